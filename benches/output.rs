@@ -0,0 +1,24 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use prettier_print::prettier_printer::PrettierPrintDisplayer;
+use std::collections::BTreeMap;
+
+fn output(c: &mut Criterion) {
+    let map: BTreeMap<String, Vec<u32>> = (0..50)
+        .map(|i| (format!("key {}", i), (0..10).collect()))
+        .collect();
+    let ascii = format!("{:#?}", map);
+    // Same shape, but every key has a character that is not ASCII
+    let unicode = ascii.replace("key", "kéy");
+
+    let mut group = c.benchmark_group("output");
+    group.bench_function("ascii", |b| {
+        b.iter(|| PrettierPrintDisplayer::<()>::output(black_box([0; 32]), black_box(&ascii)))
+    });
+    group.bench_function("unicode", |b| {
+        b.iter(|| PrettierPrintDisplayer::<()>::output(black_box([0; 32]), black_box(&unicode)))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, output);
+criterion_main!(benches);
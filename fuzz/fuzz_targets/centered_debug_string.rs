@@ -9,15 +9,26 @@ struct Input {
     terminal_size: (u8, u8),
 }
 
-fuzz_target!(|input: Input| {
-    let mut debug_string = CenteredDebugString::new(
-        &input.s,
-        (
-            input.terminal_size.0 as usize,
-            input.terminal_size.0 as usize,
-        ),
-    );
+fn drain(mut debug_string: CenteredDebugString) {
     for _ in 0..debug_string.len() {
         debug_string.next().unwrap();
     }
+}
+
+fuzz_target!(|input: Input| {
+    let terminal_size = (
+        input.terminal_size.0 as usize,
+        input.terminal_size.0 as usize,
+    );
+    drain(CenteredDebugString::new(&input.s, terminal_size));
+    drain(CenteredDebugString::new_with_width_fn(
+        &input.s,
+        terminal_size,
+        |s| s.len(),
+    ));
+    drain(CenteredDebugString::new_with_width_fn(
+        &input.s,
+        terminal_size,
+        |_| 0,
+    ));
 });
@@ -0,0 +1,169 @@
+use crossterm::style::{Attribute, Color, ResetColor, SetAttribute, SetForegroundColor};
+use crossterm::Command;
+
+/// Number of columns it takes for the rainbow to go through every hue.
+pub(crate) const RAINBOW_PERIOD: usize = 24;
+
+/// Color of the given column in a rainbow that repeats every `RAINBOW_PERIOD` columns.
+pub(crate) fn rainbow(column: usize) -> Color {
+    hue_to_rgb((column % RAINBOW_PERIOD) as f32 * 360.0 / RAINBOW_PERIOD as f32)
+}
+
+/// Converts a hue in degrees to a fully saturated and bright color.
+pub(crate) fn hue_to_rgb(hue: f32) -> Color {
+    let hue = hue.rem_euclid(360.0) / 60.0;
+    let x = 1.0 - (hue % 2.0 - 1.0).abs();
+    let (r, g, b) = match hue as u8 {
+        0 => (1.0, x, 0.0),
+        1 => (x, 1.0, 0.0),
+        2 => (0.0, 1.0, x),
+        3 => (0.0, x, 1.0),
+        4 => (x, 0.0, 1.0),
+        _ => (1.0, 0.0, x),
+    };
+    let to_u8 = |n: f32| (n * 255.0).round() as u8;
+    Color::Rgb {
+        r: to_u8(r),
+        g: to_u8(g),
+        b: to_u8(b),
+    }
+}
+
+/// Multiplies each channel of an RGB color by `factor`. Other colors are returned as is.
+pub(crate) fn scale(color: Color, factor: f32) -> Color {
+    match color {
+        Color::Rgb { r, g, b } => {
+            let scale = |n: u8| (n as f32 * factor).round() as u8;
+            Color::Rgb {
+                r: scale(r),
+                g: scale(g),
+                b: scale(b),
+            }
+        }
+        color => color,
+    }
+}
+
+/// Appends `c` to `s` in the given color. The color is not reset afterwards.
+pub(crate) fn push_colored(s: &mut String, c: char, color: Color) {
+    SetForegroundColor(color).write_ansi(s).unwrap(); // Writing to String never fails
+    s.push(c);
+}
+
+/// Appends `text` to `s` in the given color, then resets the color.
+#[cfg(feature = "tracing-subscriber")]
+pub(crate) fn push_colored_str(s: &mut String, text: &str, color: Color) {
+    SetForegroundColor(color).write_ansi(s).unwrap(); // Writing to String never fails
+    s.push_str(text);
+    push_reset(s);
+}
+
+/// Appends `text` to `s` in dim text.
+pub(crate) fn push_dim(s: &mut String, text: &str) {
+    SetAttribute(Attribute::Dim).write_ansi(s).unwrap(); // Writing to String never fails
+    s.push_str(text);
+    SetAttribute(Attribute::NormalIntensity)
+        .write_ansi(s)
+        .unwrap();
+}
+
+pub(crate) fn push_reset(s: &mut String) {
+    ResetColor.write_ansi(s).unwrap();
+}
+
+/// Splits `s`, which may contain the escape sequences written by this module, into characters and
+/// their colors. Characters that are not colored get `Color::Reset`.
+pub(crate) fn parse_colored(s: &str) -> Vec<(char, Color)> {
+    let mut result = Vec::new();
+    let mut current = Color::Reset;
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            // Parameters between '[' and the final byte, such as "38;2;255;0;0"
+            let sequence: String = chars
+                .by_ref()
+                .take_while(|c| !c.is_ascii_alphabetic())
+                .collect();
+            let params: Vec<u8> = sequence
+                .trim_start_matches('[')
+                .split(';')
+                .filter_map(|n| n.parse().ok())
+                .collect();
+            current = match params[..] {
+                [38, 2, r, g, b] => Color::Rgb { r, g, b },
+                _ => Color::Reset,
+            };
+        } else {
+            result.push((c, current));
+        }
+    }
+    result
+}
+
+/// Removes ANSI escape sequences from `s`.
+pub(crate) fn strip_ansi(s: &str) -> String {
+    let mut result = String::new();
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            // Skip until the final byte of the sequence
+            for c in chars.by_ref() {
+                if c.is_ascii_alphabetic() {
+                    break;
+                }
+            }
+        } else {
+            result.push(c);
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(0.0, (255, 0, 0))]
+    #[case(60.0, (255, 255, 0))]
+    #[case(120.0, (0, 255, 0))]
+    #[case(180.0, (0, 255, 255))]
+    #[case(240.0, (0, 0, 255))]
+    #[case(300.0, (255, 0, 255))]
+    #[case(360.0, (255, 0, 0))]
+    #[case(30.0, (255, 128, 0))]
+    fn hue_to_rgb(#[case] hue: f32, #[case] expected: (u8, u8, u8)) {
+        let (r, g, b) = expected;
+        assert_eq!(super::hue_to_rgb(hue), Color::Rgb { r, g, b });
+    }
+
+    #[test]
+    fn parse_colored() {
+        let mut s = String::new();
+        s.push('a');
+        push_colored(&mut s, 'b', rainbow(0));
+        s.push('c');
+        push_reset(&mut s);
+        s.push('d');
+        assert_eq!(
+            super::parse_colored(&s),
+            vec![
+                ('a', Color::Reset),
+                ('b', rainbow(0)),
+                ('c', rainbow(0)),
+                ('d', Color::Reset)
+            ]
+        );
+    }
+
+    #[test]
+    fn strip_ansi() {
+        let mut s = String::new();
+        push_colored(&mut s, 'a', Color::Red);
+        push_reset(&mut s);
+        s.push('b');
+        assert_ne!(s, "ab");
+        assert_eq!(super::strip_ansi(&s), "ab");
+    }
+}
@@ -0,0 +1,63 @@
+/// A line in the difference between two texts.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub(crate) enum DiffLine<'a> {
+    Unchanged(&'a str),
+    Added(&'a str),
+    Removed(&'a str),
+}
+
+/// Line-level difference between `before` and `after`, based on their longest common
+/// subsequence. Removed lines come before added lines where both change at the same place.
+pub(crate) fn diff_lines<'a>(before: &'a str, after: &'a str) -> Vec<DiffLine<'a>> {
+    let before: Vec<&str> = before.lines().collect();
+    let after: Vec<&str> = after.lines().collect();
+
+    // lengths[i][j] is the length of the longest common subsequence of before[i..] and after[j..]
+    let mut lengths = vec![vec![0usize; after.len() + 1]; before.len() + 1];
+    for i in (0..before.len()).rev() {
+        for j in (0..after.len()).rev() {
+            lengths[i][j] = if before[i] == after[j] {
+                lengths[i + 1][j + 1] + 1
+            } else {
+                lengths[i + 1][j].max(lengths[i][j + 1])
+            };
+        }
+    }
+
+    let mut result = Vec::with_capacity(before.len().max(after.len()));
+    let (mut i, mut j) = (0, 0);
+    while i < before.len() && j < after.len() {
+        if before[i] == after[j] {
+            result.push(DiffLine::Unchanged(before[i]));
+            i += 1;
+            j += 1;
+        } else if lengths[i + 1][j] >= lengths[i][j + 1] {
+            result.push(DiffLine::Removed(before[i]));
+            i += 1;
+        } else {
+            result.push(DiffLine::Added(after[j]));
+            j += 1;
+        }
+    }
+    result.extend(before[i..].iter().map(|&line| DiffLine::Removed(line)));
+    result.extend(after[j..].iter().map(|&line| DiffLine::Added(line)));
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+    use DiffLine::{Added, Removed, Unchanged};
+
+    #[rstest]
+    #[case("", "", vec![])]
+    #[case("a", "a", vec![Unchanged("a")])]
+    #[case("a", "", vec![Removed("a")])]
+    #[case("", "a", vec![Added("a")])]
+    #[case("a\nb\nc", "a\nx\nc", vec![Unchanged("a"), Removed("b"), Added("x"), Unchanged("c")])]
+    #[case("a\nb", "b\nc", vec![Removed("a"), Unchanged("b"), Added("c")])]
+    fn diff_lines(#[case] before: &str, #[case] after: &str, #[case] expected: Vec<DiffLine>) {
+        assert_eq!(super::diff_lines(before, after), expected);
+    }
+}
@@ -0,0 +1,164 @@
+use crate::prettier_printer::{PrettierConfig, PrettierPrinter, Seed};
+use crate::sparkles::{enter_screen, leave_screen};
+use crossterm::cursor::{MoveTo, MoveToNextLine};
+use crossterm::event::{read, Event, KeyCode};
+use crossterm::queue;
+use crossterm::style::Print;
+use crossterm::terminal::{Clear, ClearType};
+use rand::rngs::SmallRng;
+use std::fmt::Debug;
+use std::io::{StdoutLock, Write};
+
+/// Keeps track of the seeds that were shown so that going back returns the same seed.
+#[derive(Debug)]
+pub(crate) struct SeedCycler<'rng> {
+    rng: &'rng mut SmallRng,
+    seeds: Vec<Seed>,
+    index: usize,
+}
+
+impl<'rng> SeedCycler<'rng> {
+    pub(crate) fn new(rng: &'rng mut SmallRng) -> Self {
+        let first = PrettierPrinter::gen_seed(rng);
+        Self {
+            rng,
+            seeds: vec![first],
+            index: 0,
+        }
+    }
+
+    pub(crate) fn current(&self) -> Seed {
+        self.seeds[self.index]
+    }
+
+    /// Moves to the next seed, generating a new one if needed.
+    pub(crate) fn next(&mut self) -> Seed {
+        self.index += 1;
+        if self.index == self.seeds.len() {
+            self.seeds.push(PrettierPrinter::gen_seed(self.rng));
+        }
+        self.current()
+    }
+
+    /// Moves to the previous seed. Stays on the first seed if there is no previous seed.
+    pub(crate) fn prev(&mut self) -> Seed {
+        self.index = self.index.saturating_sub(1);
+        self.current()
+    }
+}
+
+/// Shows `what` decorated with `config` until a seed is chosen with Enter. Returns `None` if Esc
+/// or q was pressed.
+pub(crate) fn run<T>(
+    rng: &mut SmallRng,
+    config: &PrettierConfig,
+    what: &T,
+) -> std::io::Result<Option<Seed>>
+where
+    T: Debug,
+{
+    let stdout = std::io::stdout();
+    let mut stdout = stdout.lock();
+    let result = enter_screen(&mut stdout).and_then(|()| show(&mut stdout, rng, config, what));
+
+    // Restore the terminal even if showing failed
+    let cleared = queue!(stdout, Clear(ClearType::All), MoveTo(0, 0));
+    leave_screen(&mut stdout)?;
+    cleared?;
+    let result = result?;
+    if let Some(seed) = result {
+        println!("{:?}", seed);
+    }
+    Ok(result)
+}
+
+/// Draws seeds until one is chosen. See [`run()`].
+fn show<T>(
+    stdout: &mut StdoutLock,
+    rng: &mut SmallRng,
+    config: &PrettierConfig,
+    what: &T,
+) -> std::io::Result<Option<Seed>>
+where
+    T: Debug,
+{
+    let color = config.color.enabled(stdout);
+    let mut cycler = SeedCycler::new(rng);
+    let mut seed = cycler.current();
+    loop {
+        queue!(stdout, Clear(ClearType::All), MoveTo(0, 0))?;
+        for line in preview(config, seed, what, color).lines() {
+            queue!(stdout, Print(line), MoveToNextLine(1))?;
+        }
+        queue!(
+            stdout,
+            MoveToNextLine(1),
+            Print("← → change seed, Enter select, Esc quit"),
+        )?;
+        stdout.flush()?;
+
+        if let Event::Key(event) = read()? {
+            match event.code {
+                KeyCode::Left => seed = cycler.prev(),
+                KeyCode::Right => seed = cycler.next(),
+                KeyCode::Enter => return Ok(Some(seed)),
+                KeyCode::Esc | KeyCode::Char('q') => return Ok(None),
+                _ => {}
+            }
+        }
+    }
+}
+
+/// `what` decorated with `config` and `seed`, as it would be printed.
+fn preview<T>(config: &PrettierConfig, seed: Seed, what: &T, color: bool) -> String
+where
+    T: Debug,
+{
+    let mut result = String::new();
+    config
+        .render(seed, &config.debug_string(what), &mut result, color)
+        .unwrap(); // Writing to String never fails
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prettier_printer::{ColorChoice, PrettierPrintDisplayer, Style};
+    use rand::SeedableRng;
+
+    #[test]
+    fn seed_cycler() {
+        let mut rng = SmallRng::from_seed(Seed::default());
+        let mut cycler = SeedCycler::new(&mut rng);
+        let first = cycler.current();
+        assert_eq!(cycler.prev(), first);
+
+        let second = cycler.next();
+        let third = cycler.next();
+        assert_ne!(first, second);
+        assert_ne!(second, third);
+
+        assert_eq!(cycler.prev(), second);
+        assert_eq!(cycler.prev(), first);
+        assert_eq!(cycler.next(), second);
+        assert_eq!(cycler.next(), third);
+    }
+
+    #[test]
+    fn preview() {
+        let seed = [3; 32];
+        let config = PrettierConfig {
+            fill: '.',
+            style: Style::InlineCorners,
+            color: ColorChoice::Never,
+            ..PrettierConfig::default()
+        };
+        let preview = super::preview(&config, seed, &vec![1, 2], false);
+        assert_eq!(preview, config.output(seed, "[1, 2]"));
+        assert_ne!(
+            preview,
+            PrettierPrintDisplayer::<()>::output(seed, "[1, 2]")
+        );
+    }
+}
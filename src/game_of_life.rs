@@ -3,10 +3,13 @@ use rand::distributions::Standard;
 use rand::prelude::Distribution;
 use rand::rngs::SmallRng;
 use rand::{Rng, SeedableRng};
+use std::collections::VecDeque;
+use std::convert::TryFrom;
+use std::fmt::{Display, Formatter};
 use std::iter::repeat_with;
 
 #[derive(Debug, Copy, Clone, PartialEq)]
-pub(crate) enum Cell {
+pub enum Cell {
     Dead,
     Live,
 }
@@ -30,16 +33,127 @@ impl Distribution<Cell> for Standard {
     }
 }
 
+/// Error returned by [`Board::from_cells()`] and [`Board::from_bytes()`].
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum BoardError {
+    /// Width or height is zero.
+    ZeroDimension { width: usize, height: usize },
+    /// Number of cells does not equal `width * height`.
+    SizeMismatch {
+        len: usize,
+        width: usize,
+        height: usize,
+    },
+    /// The serialized board was written by an unknown version of the format.
+    UnsupportedVersion(u8),
+    /// The serialized board is shorter or longer than its header says.
+    InvalidLength { expected: usize, actual: usize },
+    /// The serialized board contains an unknown wrap mode.
+    InvalidWrapMode(u8),
+    /// `width * height` does not fit in a `usize`.
+    TooLarge { width: usize, height: usize },
+}
+
+impl Display for BoardError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BoardError::ZeroDimension { width, height } => {
+                write!(
+                    f,
+                    "board dimensions must be non-zero; got {}x{}",
+                    width, height
+                )
+            }
+            BoardError::SizeMismatch { len, width, height } => write!(
+                f,
+                "expected {} cells for a {}x{} board; got {}",
+                width * height,
+                width,
+                height,
+                len
+            ),
+            BoardError::UnsupportedVersion(version) => {
+                write!(f, "unsupported serialization version {}", version)
+            }
+            BoardError::InvalidLength { expected, actual } => write!(
+                f,
+                "expected {} bytes of serialized board; got {}",
+                expected, actual
+            ),
+            BoardError::InvalidWrapMode(n) => write!(f, "invalid wrap mode {}", n),
+            BoardError::TooLarge { width, height } => {
+                write!(f, "a {}x{} board has too many cells", width, height)
+            }
+        }
+    }
+}
+
+impl std::error::Error for BoardError {}
+
+/// How cells on the edges of the board find their neighbors.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum WrapMode {
+    /// Neighbors are found by wrapping around the flattened cell array. The cell to the left of
+    /// the first cell in a row is the last cell of the previous row, and the first row is below
+    /// the last row.
+    Wrap,
+    /// Cells outside of the board are always dead.
+    Dead,
+}
+
+/// Computes the generation after `current`, a flattened grid of cells.
+///
+/// # Panics
+///
+/// Panics if `current` does not contain exactly `width * height` cells.
+pub fn next_generation(
+    current: &[Cell],
+    width: usize,
+    height: usize,
+    wrap_mode: WrapMode,
+) -> Vec<Cell> {
+    assert_eq!(current.len(), width * height);
+    (0..current.len())
+        .map(|i| {
+            let sum = Board::live_neighbor_count(current, width, height, i, wrap_mode);
+            CONWAY_RULES[u8::from(current[i]) as usize][sum as usize]
+        })
+        .collect()
+}
+
+/// Next state of a cell, indexed by the current state (0 for dead, 1 for live) and the number of
+/// live neighbors.
+const CONWAY_RULES: [[Cell; 9]; 2] = {
+    use Cell::{Dead as D, Live as L};
+    [
+        [D, D, D, L, D, D, D, D, D], // Dead cell with 3 neighbors becomes live
+        [D, D, L, L, D, D, D, D, D], // Live cell with 2 or 3 neighbors survives
+    ]
+};
+
 /// Game of life implementation
-#[derive(Debug)]
-pub(crate) struct Board {
+#[derive(Debug, Clone)]
+pub struct Board {
     arr: Vec<Cell>,
     width: usize,
     height: usize,
+    wrap_mode: WrapMode,
+    /// Cells that may change in the next tick because a cell around them changed in the last
+    /// tick. `None` if every cell may change.
+    dirty: Option<Vec<bool>>,
+}
+
+impl PartialEq for Board {
+    fn eq(&self, other: &Self) -> bool {
+        self.arr == other.arr
+            && self.width == other.width
+            && self.height == other.height
+            && self.wrap_mode == other.wrap_mode
+    }
 }
 
 impl Board {
-    pub(crate) fn new(seed: Seed, terminal_size: (u16, u16)) -> Self {
+    pub fn new(seed: Seed, terminal_size: (u16, u16)) -> Self {
         let mut rng = SmallRng::from_seed(seed).sample_iter(Standard);
         Self {
             arr: repeat_with(|| rng.next().unwrap())
@@ -47,53 +161,414 @@ impl Board {
                 .collect(),
             width: terminal_size.0 as usize,
             height: terminal_size.1 as usize,
+            wrap_mode: WrapMode::Wrap,
+            dirty: None,
+        }
+    }
+
+    /// Creates a board from a flattened grid of cells. Returns an error if `arr` does not contain
+    /// exactly `width * height` cells or if either dimension is zero.
+    pub fn from_cells(arr: Vec<Cell>, width: usize, height: usize) -> Result<Self, BoardError> {
+        let cell_count = width.checked_mul(height);
+        if width == 0 || height == 0 {
+            Err(BoardError::ZeroDimension { width, height })
+        } else if cell_count.is_none() {
+            Err(BoardError::TooLarge { width, height })
+        } else if cell_count != Some(arr.len()) {
+            Err(BoardError::SizeMismatch {
+                len: arr.len(),
+                width,
+                height,
+            })
+        } else {
+            Ok(Self::new_with_array(arr, width, height))
         }
     }
 
-    #[allow(dead_code)] // Used in test
     fn new_with_array(arr: Vec<Cell>, width: usize, height: usize) -> Self {
-        Self { arr, width, height }
+        Self {
+            arr,
+            width,
+            height,
+            wrap_mode: WrapMode::Wrap,
+            dirty: None,
+        }
+    }
+
+    /// Sets how cells on the edges find their neighbors. Defaults to [`WrapMode::Wrap`].
+    pub fn with_wrap_mode(mut self, wrap_mode: WrapMode) -> Self {
+        self.wrap_mode = wrap_mode;
+        self.dirty = None;
+        self
+    }
+
+    /// Serializes the board. The format is:
+    ///
+    /// - 1 byte: format version, currently 1
+    /// - 1 byte: wrap mode; 0 for [`WrapMode::Wrap`] and 1 for [`WrapMode::Dead`]
+    /// - 4 bytes: width as little-endian `u32`
+    /// - 4 bytes: height as little-endian `u32`
+    /// - The cells, 8 per byte, starting from the least significant bit. 1 is live.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the width or height do not fit in a `u32`.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut result = Vec::with_capacity(SERIALIZED_HEADER_LENGTH + (self.arr.len() + 7) / 8);
+        result.push(SERIALIZATION_VERSION);
+        result.push(match self.wrap_mode {
+            WrapMode::Wrap => 0,
+            WrapMode::Dead => 1,
+        });
+        for n in [self.width, self.height] {
+            let n = u32::try_from(n).expect("board is too large to serialize");
+            result.extend_from_slice(&n.to_le_bytes());
+        }
+        for chunk in self.arr.chunks(8) {
+            let byte = chunk
+                .iter()
+                .enumerate()
+                .fold(0, |byte, (i, &cell)| byte | u8::from(cell) << i);
+            result.push(byte);
+        }
+        result
+    }
+
+    /// Deserializes a board that was serialized with [`Board::to_bytes()`].
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, BoardError> {
+        if bytes.len() < SERIALIZED_HEADER_LENGTH {
+            return Err(BoardError::InvalidLength {
+                expected: SERIALIZED_HEADER_LENGTH,
+                actual: bytes.len(),
+            });
+        }
+        if bytes[0] != SERIALIZATION_VERSION {
+            return Err(BoardError::UnsupportedVersion(bytes[0]));
+        }
+        let wrap_mode = match bytes[1] {
+            0 => WrapMode::Wrap,
+            1 => WrapMode::Dead,
+            n => return Err(BoardError::InvalidWrapMode(n)),
+        };
+        let read_u32 = |i: usize| {
+            u32::from_le_bytes([bytes[i], bytes[i + 1], bytes[i + 2], bytes[i + 3]]) as usize
+        };
+        let (width, height) = (read_u32(2), read_u32(6));
+
+        let cell_count = width
+            .checked_mul(height)
+            .ok_or(BoardError::TooLarge { width, height })?;
+        let cell_bytes = &bytes[SERIALIZED_HEADER_LENGTH..];
+        let expected = cell_count / 8 + usize::from(cell_count % 8 != 0);
+        if cell_bytes.len() != expected {
+            return Err(BoardError::InvalidLength {
+                expected: SERIALIZED_HEADER_LENGTH + expected,
+                actual: bytes.len(),
+            });
+        }
+        let arr = (0..cell_count)
+            .map(|i| {
+                if cell_bytes[i / 8] >> (i % 8) & 1 == 1 {
+                    Cell::Live
+                } else {
+                    Cell::Dead
+                }
+            })
+            .collect();
+        Ok(Board::from_cells(arr, width, height)?.with_wrap_mode(wrap_mode))
+    }
+
+    /// Replaces every cell with a random cell.
+    pub fn randomize(&mut self, seed: Seed) {
+        let mut rng = SmallRng::from_seed(seed).sample_iter(Standard);
+        for cell in self.arr.iter_mut() {
+            *cell = rng.next().unwrap();
+        }
+        self.dirty = None;
+    }
+
+    /// Replaces the cells within `radius` of `center` with random cells, each live with
+    /// probability `density`. Cells past the edges are skipped with [`WrapMode::Dead`] and found
+    /// by wrapping around with [`WrapMode::Wrap`], like neighbors are.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `center` is outside of the board or `density` is not between 0 and 1.
+    pub fn splatter(&mut self, center: (usize, usize), radius: usize, seed: Seed, density: f64) {
+        assert!(
+            center.0 < self.width && center.1 < self.height,
+            "center {:?} is outside of the {}x{} board",
+            center,
+            self.width,
+            self.height
+        );
+        assert!(
+            (0.0..=1.0).contains(&density),
+            "density must be between 0 and 1; got {}",
+            density
+        );
+
+        let mut rng = SmallRng::from_seed(seed);
+        // Every cell is closer than width + height to the center
+        let radius = radius.min(self.width + self.height) as isize;
+        let (x, y) = (center.0 as isize, center.1 as isize);
+        let (width, height) = (self.width as isize, self.height as isize);
+        // Offsets of the cells on the board, visiting each cell once
+        let (dx_range, dy_range) = match self.wrap_mode {
+            WrapMode::Dead => (-x..=width - 1 - x, -y..=height - 1 - y),
+            WrapMode::Wrap => (
+                -(width / 2)..=width - 1 - width / 2,
+                -(height / 2)..=height - 1 - height / 2,
+            ),
+        };
+        let dx_range = (-radius).max(*dx_range.start())..=radius.min(*dx_range.end());
+        let dy_range = (-radius).max(*dy_range.start())..=radius.min(*dy_range.end());
+        for dy in dy_range {
+            for dx in dx_range.clone() {
+                if dx * dx + dy * dy > radius * radius {
+                    continue;
+                }
+                let (nx, ny) = (x + dx, y + dy);
+                let index = match self.wrap_mode {
+                    WrapMode::Dead => {
+                        if !(0..self.width as isize).contains(&nx)
+                            || !(0..self.height as isize).contains(&ny)
+                        {
+                            continue;
+                        }
+                        ny as usize * self.width + nx as usize
+                    }
+                    WrapMode::Wrap => Board::wrap_around_index(
+                        self.width,
+                        self.height,
+                        ny * self.width as isize + nx,
+                    ),
+                };
+                self.arr[index] = if rng.gen_bool(density) {
+                    Cell::Live
+                } else {
+                    Cell::Dead
+                };
+            }
+        }
+        self.dirty = None;
+    }
+
+    /// Number of live cells.
+    pub fn live_count(&self) -> usize {
+        self.arr.iter().filter(|&&cell| cell == Cell::Live).count()
     }
 
     /// Flattened output grid
-    pub(crate) fn cell_array(&self) -> &[Cell] {
+    pub fn cell_array(&self) -> &[Cell] {
         &self.arr
     }
 
     /// width * height != 0
     fn wrap_around_index(width: usize, height: usize, index: isize) -> usize {
         debug_assert_ne!(width * height, 0);
-        (((width * height) as isize + index) % (width * height) as isize).abs() as usize
+        index.rem_euclid((width * height) as isize) as usize
     }
 
-    /// Step one frame
-    pub(crate) fn tick(&mut self) {
-        let original = self.arr.clone();
+    /// Counts live cells around `index`. On boards that are less than 3 cells wide or tall, the
+    /// same cell can be reached from more than one direction by wrapping around. Each distinct
+    /// cell is only counted once, and a cell is never counted as its own neighbor.
+    fn live_neighbor_count(
+        cells: &[Cell],
+        width: usize,
+        height: usize,
+        index: usize,
+        wrap_mode: WrapMode,
+    ) -> u8 {
+        if wrap_mode == WrapMode::Dead {
+            let (x, y) = ((index % width) as isize, (index / width) as isize);
+            let mut sum = 0;
+            for (dx, dy) in NEIGHBOR_OFFSETS {
+                let (nx, ny) = (x + dx, y + dy);
+                if (0..width as isize).contains(&nx) && (0..height as isize).contains(&ny) {
+                    sum += u8::from(cells[ny as usize * width + nx as usize]);
+                }
+            }
+            return sum;
+        }
 
-        let width = self.width as isize;
+        let i = index as isize;
+        let w = width as isize;
+        let mut neighbors = [
+            i - w - 1,
+            i - w,
+            i - w + 1,
+            i - 1,
+            i + 1,
+            i + w - 1,
+            i + w,
+            i + w + 1,
+        ]
+        .map(|n| Board::wrap_around_index(width, height, n));
 
-        for i in 0..original.len() as isize {
-            let sum = {
-                let index = |index: isize| Board::wrap_around_index(self.width, self.height, index);
-                u8::from(original[index(i - width - 1)])
-                    + u8::from(original[index(i - width)])
-                    + u8::from(original[index(i - width + 1)])
-                    + u8::from(original[index(i - 1)])
-                    + u8::from(original[index(i + 1)])
-                    + u8::from(original[index(i + width - 1)])
-                    + u8::from(original[index(i + width)])
-                    + u8::from(original[index(i + width + 1)])
-            };
+        if width < 3 || height < 3 {
+            neighbors.sort_unstable();
+            let mut sum = 0;
+            let mut prev = None;
+            for n in neighbors {
+                if prev != Some(n) && n != index {
+                    sum += u8::from(cells[n]);
+                }
+                prev = Some(n);
+            }
+            sum
+        } else {
+            neighbors.iter().map(|&n| u8::from(cells[n])).sum()
+        }
+    }
 
-            if !matches!(sum, 2 | 3) {
-                self.arr[i as usize] = Cell::Dead;
-            } else if original[i as usize] == Cell::Dead && sum == 3 {
-                self.arr[i as usize] = Cell::Live;
+    /// Calls `f` with each distinct neighbor of `index`, following the same rules as
+    /// [`Board::live_neighbor_count()`].
+    fn for_each_neighbor<F>(
+        width: usize,
+        height: usize,
+        index: usize,
+        wrap_mode: WrapMode,
+        mut f: F,
+    ) where
+        F: FnMut(usize),
+    {
+        let (x, y) = ((index % width) as isize, (index / width) as isize);
+        for (dx, dy) in NEIGHBOR_OFFSETS {
+            match wrap_mode {
+                WrapMode::Dead => {
+                    let (nx, ny) = (x + dx, y + dy);
+                    if (0..width as isize).contains(&nx) && (0..height as isize).contains(&ny) {
+                        f(ny as usize * width + nx as usize);
+                    }
+                }
+                WrapMode::Wrap => {
+                    let n = index as isize + dy * width as isize + dx;
+                    f(Board::wrap_around_index(width, height, n));
+                }
+            }
+        }
+    }
+
+    /// Step one frame. Only the cells around the cells that changed in the last tick are
+    /// recomputed, since the others have the same neighbors as before.
+    pub fn tick(&mut self) {
+        let mut next = self.arr.clone();
+        let mut next_dirty = vec![false; self.arr.len()];
+        for i in 0..self.arr.len() {
+            if matches!(&self.dirty, Some(dirty) if !dirty[i]) {
+                continue;
+            }
+            let sum =
+                Board::live_neighbor_count(&self.arr, self.width, self.height, i, self.wrap_mode);
+            next[i] = CONWAY_RULES[u8::from(self.arr[i]) as usize][sum as usize];
+            if next[i] != self.arr[i] {
+                // The cell and its neighbors may change in the next tick
+                next_dirty[i] = true;
+                Board::for_each_neighbor(self.width, self.height, i, self.wrap_mode, |n| {
+                    next_dirty[n] = true
+                });
+            }
+        }
+        self.arr = next;
+        self.dirty = Some(next_dirty);
+    }
+
+    /// Returns the next frame as a new board, leaving `self` unchanged.
+    pub fn evolve(&self) -> Board {
+        let mut board = self.clone();
+        board.tick();
+        board
+    }
+}
+
+impl<'board> IntoIterator for &'board Board {
+    type Item = (usize, usize, Cell);
+    type IntoIter = Cells<'board>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        Cells {
+            inner: self.arr.iter().enumerate(),
+            width: self.width,
+        }
+    }
+}
+
+/// Iterator over the cells of a [`Board`] as `(x, y, cell)`, row by row from the top left.
+#[derive(Debug, Clone)]
+pub struct Cells<'board> {
+    inner: std::iter::Enumerate<std::slice::Iter<'board, Cell>>,
+    width: usize,
+}
+
+impl Iterator for Cells<'_> {
+    type Item = (usize, usize, Cell);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner
+            .next()
+            .map(|(i, &cell)| (i % self.width, i / self.width, cell))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl ExactSizeIterator for Cells<'_> {}
+
+/// Detects boards that repeat, such as still lifes and oscillators, by remembering the last few
+/// states.
+#[derive(Debug, Clone)]
+pub struct PeriodDetector {
+    history: VecDeque<Vec<Cell>>,
+    capacity: usize,
+}
+
+impl PeriodDetector {
+    /// Remembers up to `capacity` states, so periods up to `capacity` can be detected.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            history: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    /// Records `board` and returns the smallest number of generations since the board was in
+    /// the same state, if it was in the last `capacity` generations. A still life has period 1.
+    pub fn observe(&mut self, board: &Board) -> Option<usize> {
+        let cells = board.cell_array();
+        let period = self
+            .history
+            .iter()
+            .rev()
+            .position(|state| state == cells)
+            .map(|i| i + 1);
+
+        if self.capacity > 0 {
+            if self.history.len() == self.capacity {
+                self.history.pop_front();
             }
+            self.history.push_back(cells.to_vec());
         }
+        period
     }
 }
 
+const SERIALIZATION_VERSION: u8 = 1;
+const SERIALIZED_HEADER_LENGTH: usize = 10;
+
+const NEIGHBOR_OFFSETS: [(isize, isize); 8] = [
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+    (-1, 0),
+    (1, 0),
+    (-1, 1),
+    (0, 1),
+    (1, 1),
+];
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -182,14 +657,145 @@ mod tests {
             vec![0, 0, 0, 0],
         ],
     )]
+    // Boards less than 3 cells wide or tall
+    #[case( // 8
+        vec![
+            vec![1, 1, 1],
+        ],
+        vec![
+            vec![1, 1, 1],
+        ],
+    )]
+    #[case( // 9
+        vec![
+            vec![0, 1, 0],
+        ],
+        vec![
+            vec![0, 0, 0],
+        ],
+    )]
+    #[case( // 10
+        vec![
+            vec![1],
+            vec![1],
+            vec![1],
+        ],
+        vec![
+            vec![1],
+            vec![1],
+            vec![1],
+        ],
+    )]
+    #[case( // 11
+        vec![
+            vec![1],
+            vec![1],
+            vec![0],
+        ],
+        vec![
+            vec![0],
+            vec![0],
+            vec![0],
+        ],
+    )]
+    #[case( // 12
+        vec![
+            vec![1, 1],
+            vec![1, 0],
+        ],
+        vec![
+            vec![1, 1],
+            vec![1, 1],
+        ],
+    )]
     fn tick(#[case] initial_cells: Vec<Vec<u8>>, #[case] expected_cells: Vec<Vec<u8>>) {
         let mut board = Board::new_with_array(
             convert_to_array(&initial_cells),
-            initial_cells.get(0).map(|a| a.len()).unwrap_or_default(),
+            initial_cells.first().map(|a| a.len()).unwrap_or_default(),
             initial_cells.len(),
         );
         board.tick();
         assert_eq!(board.arr, convert_to_array(&expected_cells));
+
+        assert_eq!(
+            next_generation(
+                &convert_to_array(&initial_cells),
+                board.width,
+                board.height,
+                WrapMode::Wrap,
+            ),
+            convert_to_array(&expected_cells),
+        );
+    }
+
+    #[rstest]
+    #[case(
+        vec![
+            vec![0, 0, 0],
+            vec![1, 1, 1],
+            vec![0, 0, 0],
+        ],
+        vec![
+            vec![0, 1, 0],
+            vec![0, 1, 0],
+            vec![0, 1, 0],
+        ],
+    )]
+    #[case(
+        vec![
+            vec![1, 1, 1],
+            vec![0, 0, 0],
+            vec![0, 0, 0],
+        ],
+        vec![
+            vec![0, 1, 0],
+            vec![0, 1, 0],
+            vec![0, 0, 0],
+        ],
+    )]
+    #[case(
+        vec![
+            vec![1, 0, 0, 1],
+            vec![1, 0, 0, 1],
+        ],
+        vec![
+            vec![0, 0, 0, 0],
+            vec![0, 0, 0, 0],
+        ],
+    )]
+    fn next_generation_dead_edges(
+        #[case] initial_cells: Vec<Vec<u8>>,
+        #[case] expected_cells: Vec<Vec<u8>>,
+    ) {
+        let result = next_generation(
+            &convert_to_array(&initial_cells),
+            initial_cells[0].len(),
+            initial_cells.len(),
+            WrapMode::Dead,
+        );
+        assert_eq!(result, convert_to_array(&expected_cells));
+    }
+
+    #[test]
+    fn conway_rules() {
+        fn next_cell(cell: Cell, sum: u8) -> Cell {
+            if !matches!(sum, 2 | 3) {
+                Cell::Dead
+            } else if cell == Cell::Dead && sum == 3 {
+                Cell::Live
+            } else {
+                cell
+            }
+        }
+
+        for cell in [Cell::Dead, Cell::Live] {
+            for sum in 0..=8 {
+                assert_eq!(
+                    CONWAY_RULES[u8::from(cell) as usize][sum as usize],
+                    next_cell(cell, sum)
+                );
+            }
+        }
     }
 
     fn convert_to_array(array: &[Vec<u8>]) -> Vec<Cell> {
@@ -219,6 +825,8 @@ mod tests {
     #[case(2, 1, -1, 1)]
     #[case(2, 1, -2, 0)]
     #[case(2, 1, -3, 1)]
+    #[case(3, 1, -4, 2)]
+    #[case(3, 1, 4, 1)]
     fn wrap_around_index(
         #[case] width: usize,
         #[case] height: usize,
@@ -228,6 +836,257 @@ mod tests {
         assert_eq!(Board::wrap_around_index(width, height, index), expected);
     }
 
+    #[rstest]
+    #[case(vec![Cell::Dead], 1, 1, Ok(()))]
+    #[case(vec![Cell::Dead; 6], 3, 2, Ok(()))]
+    #[case(vec![], 0, 0, Err(BoardError::ZeroDimension { width: 0, height: 0 }))]
+    #[case(vec![], 0, 1, Err(BoardError::ZeroDimension { width: 0, height: 1 }))]
+    #[case(vec![], 1, 0, Err(BoardError::ZeroDimension { width: 1, height: 0 }))]
+    #[case(
+        vec![Cell::Dead; 5],
+        3,
+        2,
+        Err(BoardError::SizeMismatch { len: 5, width: 3, height: 2 }),
+    )]
+    #[case(
+        vec![Cell::Dead; 7],
+        3,
+        2,
+        Err(BoardError::SizeMismatch { len: 7, width: 3, height: 2 }),
+    )]
+    #[case(
+        vec![],
+        usize::MAX,
+        2,
+        Err(BoardError::TooLarge { width: usize::MAX, height: 2 }),
+    )]
+    fn from_cells(
+        #[case] arr: Vec<Cell>,
+        #[case] width: usize,
+        #[case] height: usize,
+        #[case] expected: Result<(), BoardError>,
+    ) {
+        let result = Board::from_cells(arr.clone(), width, height);
+        assert_eq!(result.as_ref().map(|_| ()).map_err(|e| *e), expected);
+        if let Ok(board) = result {
+            assert_eq!(board.cell_array(), arr.as_slice());
+        }
+    }
+
+    #[test]
+    fn period_detector() {
+        use Cell::{Dead, Live};
+        #[rustfmt::skip]
+        let blinker = vec![
+            Dead, Dead, Dead, Dead, Dead,
+            Dead, Dead, Live, Dead, Dead,
+            Dead, Dead, Live, Dead, Dead,
+            Dead, Dead, Live, Dead, Dead,
+            Dead, Dead, Dead, Dead, Dead,
+        ];
+        #[rustfmt::skip]
+        let block = vec![
+            Dead, Dead, Dead, Dead,
+            Dead, Live, Live, Dead,
+            Dead, Live, Live, Dead,
+            Dead, Dead, Dead, Dead,
+        ];
+
+        let mut board = Board::from_cells(blinker, 5, 5).unwrap();
+        let mut detector = PeriodDetector::new(4);
+        assert_eq!(detector.observe(&board), None);
+        board.tick();
+        assert_eq!(detector.observe(&board), None);
+        board.tick();
+        assert_eq!(detector.observe(&board), Some(2));
+
+        let mut board = Board::from_cells(block, 4, 4).unwrap();
+        let mut detector = PeriodDetector::new(4);
+        assert_eq!(detector.observe(&board), None);
+        board.tick();
+        assert_eq!(detector.observe(&board), Some(1));
+
+        // Periods longer than the history are not detected
+        let mut detector = PeriodDetector::new(1);
+        let mut board = Board::from_cells(board.cell_array().to_vec(), 4, 4).unwrap();
+        board.arr[0] = Live;
+        detector.observe(&board);
+        board.arr[0] = Dead;
+        detector.observe(&board);
+        board.arr[0] = Live;
+        assert_eq!(detector.observe(&board), None);
+    }
+
+    #[test]
+    fn evolve() {
+        let board = Board::new([3; 32], (8, 6));
+        let original = board.clone();
+        let evolved = board.evolve();
+        assert_eq!(board, original);
+
+        let mut ticked = board.clone();
+        ticked.tick();
+        assert_eq!(evolved, ticked);
+        assert_ne!(evolved, board);
+    }
+
+    #[rstest]
+    #[case((40, 30), WrapMode::Wrap)]
+    #[case((40, 30), WrapMode::Dead)]
+    #[case((2, 2), WrapMode::Wrap)]
+    #[case((1, 5), WrapMode::Wrap)]
+    #[case((7, 2), WrapMode::Dead)]
+    fn tick_matches_next_generation(#[case] size: (u16, u16), #[case] wrap_mode: WrapMode) {
+        for seed in 0..5 {
+            let mut board = Board::new([seed; 32], size).with_wrap_mode(wrap_mode);
+            let mut expected = board.cell_array().to_vec();
+            for generation in 0..200 {
+                if generation == 100 {
+                    board.randomize([seed + 1; 32]);
+                    expected = board.cell_array().to_vec();
+                }
+                board.tick();
+                expected = next_generation(&expected, size.0 as usize, size.1 as usize, wrap_mode);
+                assert_eq!(board.cell_array(), &expected[..]);
+            }
+        }
+    }
+
+    #[test]
+    fn splatter() {
+        let empty = Board::from_cells(vec![Cell::Dead; 20 * 10], 20, 10)
+            .unwrap()
+            .with_wrap_mode(WrapMode::Dead);
+        let mut board = empty.clone();
+        board.splatter((10, 5), 3, [1; 32], 0.5);
+        for (x, y, cell) in &board {
+            let (dx, dy) = (x as isize - 10, y as isize - 5);
+            if dx * dx + dy * dy > 9 {
+                assert_eq!(cell, Cell::Dead, "({}, {})", x, y);
+            }
+        }
+        assert!(board.live_count() > 0);
+
+        let mut same = empty.clone();
+        same.splatter((10, 5), 3, [1; 32], 0.5);
+        assert_eq!(board, same);
+
+        let mut full = empty.clone();
+        full.splatter((0, 0), 2, [1; 32], 1.0);
+        // Quarter of a circle of radius 2 in the corner
+        assert_eq!(full.live_count(), 6);
+
+        let mut wrapped = Board::from_cells(vec![Cell::Dead; 20 * 10], 20, 10).unwrap();
+        wrapped.splatter((0, 0), 1, [1; 32], 1.0);
+        let live: Vec<usize> = (0..20 * 10)
+            .filter(|&i| wrapped.cell_array()[i] == Cell::Live)
+            .collect();
+        // The cell to the left of the first cell is the last cell, like neighbors
+        assert_eq!(live, [0, 1, 20, 180, 199]);
+
+        // The radius is clamped to the board, so a huge radius covers it without taking forever
+        for wrap_mode in [WrapMode::Dead, WrapMode::Wrap] {
+            let mut huge = empty.clone().with_wrap_mode(wrap_mode);
+            huge.splatter((3, 7), usize::MAX, [1; 32], 1.0);
+            assert_eq!(huge.live_count(), 20 * 10);
+
+            let mut clamped = empty.clone().with_wrap_mode(wrap_mode);
+            clamped.splatter((3, 7), 30, [1; 32], 0.5);
+            let mut huge = empty.clone().with_wrap_mode(wrap_mode);
+            huge.splatter((3, 7), usize::MAX, [1; 32], 0.5);
+            assert_eq!(huge, clamped);
+        }
+    }
+
+    #[test]
+    fn into_iter() {
+        use Cell::{Dead, Live};
+        let board = Board::from_cells(vec![Live, Dead, Dead, Dead, Live, Live], 3, 2).unwrap();
+        let cells: Vec<(usize, usize, Cell)> = board.into_iter().collect();
+        assert_eq!(
+            cells,
+            vec![
+                (0, 0, Live),
+                (1, 0, Dead),
+                (2, 0, Dead),
+                (0, 1, Dead),
+                (1, 1, Live),
+                (2, 1, Live),
+            ]
+        );
+        assert_eq!((&board).into_iter().len(), 6);
+    }
+
+    #[rstest]
+    #[case(1, 1)]
+    #[case(3, 2)]
+    #[case(8, 1)]
+    #[case(5, 7)]
+    fn bytes_round_trip(#[case] width: usize, #[case] height: usize) {
+        use rand::rngs::SmallRng;
+        let mut rng = SmallRng::from_seed(Seed::default());
+        for wrap_mode in [WrapMode::Wrap, WrapMode::Dead] {
+            let arr: Vec<Cell> = (0..width * height).map(|_| rng.gen()).collect();
+            let board = Board::from_cells(arr, width, height)
+                .unwrap()
+                .with_wrap_mode(wrap_mode);
+            let bytes = board.to_bytes();
+            assert_eq!(bytes.len(), 10 + (width * height + 7) / 8);
+
+            let result = Board::from_bytes(&bytes).unwrap();
+            assert_eq!(result.cell_array(), board.cell_array());
+            assert_eq!(result.width, width);
+            assert_eq!(result.height, height);
+            assert_eq!(result.wrap_mode, wrap_mode);
+        }
+    }
+
+    #[rstest]
+    #[case(&[], BoardError::InvalidLength { expected: 10, actual: 0 })]
+    #[case(&[2, 0, 1, 0, 0, 0, 1, 0, 0, 0, 0], BoardError::UnsupportedVersion(2))]
+    #[case(&[1, 2, 1, 0, 0, 0, 1, 0, 0, 0, 0], BoardError::InvalidWrapMode(2))]
+    #[case(
+        &[1, 0, 1, 0, 0, 0, 1, 0, 0, 0],
+        BoardError::InvalidLength { expected: 11, actual: 10 },
+    )]
+    #[case(
+        &[1, 0, 0, 0, 0, 0, 1, 0, 0, 0],
+        BoardError::ZeroDimension { width: 0, height: 1 },
+    )]
+    fn from_bytes_invalid(#[case] bytes: &[u8], #[case] expected: BoardError) {
+        assert_eq!(Board::from_bytes(bytes).unwrap_err(), expected);
+    }
+
+    #[test]
+    fn from_bytes_max_dimensions() {
+        let bytes = [1, 0, 255, 255, 255, 255, 255, 255, 255, 255];
+        let width = u32::MAX as usize;
+        let expected = match width.checked_mul(width) {
+            Some(cell_count) => BoardError::InvalidLength {
+                expected: 10 + cell_count / 8 + 1,
+                actual: 10,
+            },
+            None => BoardError::TooLarge {
+                width,
+                height: width,
+            },
+        };
+        assert_eq!(Board::from_bytes(&bytes).unwrap_err(), expected);
+        assert!(!expected.to_string().is_empty());
+    }
+
+    #[test]
+    fn randomize() {
+        let mut board = Board::from_cells(vec![Cell::Dead; 100], 10, 10).unwrap();
+        assert_eq!(board.live_count(), 0);
+        board.randomize(Seed::default());
+        assert_ne!(board.live_count(), 0);
+        assert_eq!(
+            board.cell_array(),
+            Board::new(Seed::default(), (10, 10)).cell_array()
+        );
+    }
+
     #[test]
     fn wrap_around_index_invalid() {
         assert!(catch_unwind_silent(|| Board::wrap_around_index(0, 0, 0)).is_err());
@@ -0,0 +1,151 @@
+//! Utilities for arranging several rendered boxes together.
+
+use crate::prettier_printer::{PrettierConfig, PrettierPrinter, Seed};
+use std::iter::repeat;
+
+/// Places `blocks` on top of each other.
+pub fn join_vertical(blocks: &[&str]) -> String {
+    let mut result = String::new();
+    for line in blocks.iter().flat_map(|block| block.lines()) {
+        result.push_str(line);
+        result.push('\n');
+    }
+    result
+}
+
+/// Places `blocks` next to each other, with `gap` columns between them. Blocks are aligned at the
+/// top. Lines are measured with `display_width`, such as [`PrettierConfig::display_width()`] of
+/// the config that decorated the blocks.
+pub fn join_horizontal<F>(blocks: &[&str], gap: usize, display_width: F) -> String
+where
+    F: Fn(&str) -> usize,
+{
+    let blocks: Vec<Vec<&str>> = blocks.iter().map(|block| block.lines().collect()).collect();
+    let widths: Vec<usize> = blocks
+        .iter()
+        .map(|lines| {
+            lines
+                .iter()
+                .map(|line| display_width(line))
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+    let height = blocks.iter().map(|lines| lines.len()).max().unwrap_or(0);
+
+    let mut result = String::new();
+    for row in 0..height {
+        let mut line = String::new();
+        for (i, lines) in blocks.iter().enumerate() {
+            if i > 0 {
+                line.extend(repeat(' ').take(gap));
+            }
+            let s = lines.get(row).copied().unwrap_or("");
+            line.push_str(s);
+            line.extend(repeat(' ').take(widths[i] - display_width(s)));
+        }
+        result.push_str(line.trim_end_matches(' '));
+        result.push('\n');
+    }
+    result
+}
+
+/// Splits the lines of `text` into `column_count` columns placed side by side, like a newspaper.
+/// Each column takes an equal share of the lines, except the last, which may have fewer. Text with
+/// fewer lines than `column_count` results in fewer columns.
+pub(crate) fn split_columns<F>(
+    text: &str,
+    column_count: usize,
+    gap: usize,
+    display_width: F,
+) -> String
+where
+    F: Fn(&str) -> usize,
+{
+    let lines: Vec<&str> = text.lines().collect();
+    let column_count = column_count.max(1);
+    let rows = ((lines.len() + column_count - 1) / column_count).max(1);
+    let columns: Vec<String> = lines.chunks(rows).map(|chunk| chunk.join("\n")).collect();
+    let columns: Vec<&str> = columns.iter().map(String::as_str).collect();
+    join_horizontal(&columns, gap, display_width)
+}
+
+/// Surrounds `composite`, which is usually several boxes joined with [`join_vertical()`] or
+/// [`join_horizontal()`], with another box decorated according to `config` and `seed`.
+///
+/// # Panics
+///
+/// Panics if `config` is not accepted by [`PrettierPrinter::with_config()`].
+pub fn envelope(composite: &str, seed: Seed, config: &PrettierConfig) -> String {
+    PrettierPrinter::new_with_seed(seed)
+        .with_config(config.clone())
+        .print_display(&composite)
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(&[], "")]
+    #[case(&["a"], "a\n")]
+    #[case(&["a\n", "b\nc\n"], "a\nb\nc\n")]
+    fn join_vertical(#[case] blocks: &[&str], #[case] expected: &str) {
+        assert_eq!(super::join_vertical(blocks), expected);
+    }
+
+    #[rstest]
+    #[case(&[], 1, PrettierConfig::default(), "")]
+    #[case(&["a\n"], 1, PrettierConfig::default(), "a\n")]
+    #[case(&["a\nbb\n", "c\n"], 1, PrettierConfig::default(), "a  c\nbb\n")]
+    #[case(&["🌈\n", "c\nd\n"], 2, PrettierConfig::default(), "🌈  c\n    d\n")]
+    #[case(&["🌈\n", "c\nd\n"], 2, PrettierPrinter::default().with_emoji_width(1).config().clone(), "🌈  c\n   d\n")]
+    #[case(&["日本\n", "c\nd\n"], 1, PrettierPrinter::default().with_width_fn(|_| 1).config().clone(), "日本 c\n   d\n")]
+    fn join_horizontal(
+        #[case] blocks: &[&str],
+        #[case] gap: usize,
+        #[case] config: PrettierConfig,
+        #[case] expected: &str,
+    ) {
+        let result = super::join_horizontal(blocks, gap, |s| config.display_width(s));
+        assert_eq!(result, expected);
+    }
+
+    #[rstest]
+    #[case("", 2, "")]
+    #[case("a\n", 2, "a\n")]
+    #[case("a\nb\n", 3, "a b\n")]
+    #[case("a\nb\nc\n", 2, "a c\nb\n")]
+    #[case("a\nbb\nc\nd\n", 2, "a  c\nbb d\n")]
+    fn split_columns(#[case] text: &str, #[case] column_count: usize, #[case] expected: &str) {
+        let display_width = |s: &str| s.chars().count();
+        assert_eq!(
+            super::split_columns(text, column_count, 1, display_width),
+            expected
+        );
+    }
+
+    #[test]
+    fn envelope() {
+        let mut printer = PrettierPrinter::new_with_seed([0; 32]);
+        let first = printer.print(&1).to_string();
+        let second = printer.print(&vec!["a", "b"]).to_string();
+        let composite = super::join_vertical(&[&first, &second]);
+        let result = super::envelope(&composite, [0; 32], &PrettierConfig::default());
+        let expected = r#"🌈              🌈
+ 🌈 🌈
+ ⭐1     🌟
+ 🌈 🌈
+ 🌈        🌈
+  [⭐
+  ⭐   "a", 🌟
+     ⭐"b",⭐
+  ]⭐           🌟
+ 🌈        🌈
+🌈              🌈
+"#;
+        assert_eq!(result, expected);
+    }
+}
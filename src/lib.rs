@@ -1,5 +1,15 @@
 #![doc = include_str!("../README.md")]
 
-mod game_of_life;
+mod color;
+mod diff;
+mod gallery;
+pub mod game_of_life;
+pub mod layout;
+mod outline;
+pub mod preset;
 pub mod prettier_printer;
 pub mod sparkles;
+#[cfg(feature = "tracing-subscriber")]
+pub mod tracing_format;
+pub mod twinkle;
+mod width;
@@ -0,0 +1,417 @@
+use std::iter::repeat;
+
+/// Shortens a `{:#?}` string by keeping only the first `max_items` items of each list, set, and
+/// map. The rest are replaced with a line that says how many items were left out. Structs and
+/// tuples are left alone.
+pub(crate) fn summarize_collections(debug_str: &str, max_items: usize) -> String {
+    let mut result = String::new();
+    // Blocks that contain the current line, from the outermost
+    let mut blocks: Vec<Block> = Vec::new();
+    for line in debug_str.lines() {
+        let content = line.trim_start_matches(' ');
+        let indent = line.len() - content.len();
+
+        let is_closing = blocks.last().map_or(false, |block| {
+            indent == block.indent && content.starts_with([']', '}', ')'])
+        });
+        if is_closing {
+            let block = blocks.pop().unwrap();
+            let hidden_count = block.hidden_count(max_items);
+            if hidden_count > 0 && !is_hidden(&blocks, max_items) {
+                result.extend(repeat(' ').take(block.indent + 4));
+                let noun = if hidden_count == 1 { "item" } else { "items" };
+                result.push_str(&format!(".. {} more {} ..\n", hidden_count, noun));
+            }
+        } else if let Some(block) = blocks.last_mut() {
+            if indent == block.indent + 4 {
+                block.item_count += 1;
+            }
+        }
+
+        if !is_hidden(&blocks, max_items) {
+            result.push_str(line);
+            result.push('\n');
+        }
+
+        if content.ends_with(['[', '{', '(']) {
+            blocks.push(Block {
+                indent,
+                is_collection: content.ends_with('[') || content == "{" || content.ends_with(": {"),
+                item_count: 0,
+            });
+        }
+    }
+    result
+}
+
+/// Sorts the items of every map and set in a `{:#?}` string by their text, so that the order does
+/// not depend on hashing. Lists, structs, and tuples keep their order.
+pub(crate) fn sort_collections(debug_str: &str) -> String {
+    let lines: Vec<&str> = debug_str.lines().collect();
+    sorted_items(&lines, false).concat()
+}
+
+/// Splits `lines` into items that start at the indent of the first line, sorting the items of
+/// nested maps and sets. Sorts the items themselves if `sort` is true. Each item ends with a line
+/// break.
+fn sorted_items(lines: &[&str], sort: bool) -> Vec<String> {
+    let indent_of = |line: &str| line.len() - line.trim_start_matches(' ').len();
+    let mut items = Vec::new();
+    let mut i = 0;
+    while i < lines.len() {
+        let indent = indent_of(lines[i]);
+        let content = &lines[i][indent..];
+        let mut item = format!("{}\n", lines[i]);
+        i += 1;
+        if content.ends_with(['[', '{', '(']) {
+            let end = (i..lines.len())
+                .find(|&j| indent_of(lines[j]) == indent)
+                .unwrap_or(lines.len());
+            let is_map_or_set = content == "{" || content.ends_with(": {");
+            for inner in sorted_items(&lines[i..end], is_map_or_set) {
+                item.push_str(&inner);
+            }
+            if let Some(closing) = lines.get(end) {
+                item.push_str(closing);
+                item.push('\n');
+            }
+            i = end + 1;
+        }
+        items.push(item);
+    }
+    if sort {
+        items.sort();
+    }
+    items
+}
+
+/// Prefix for each line of a `{:#?}` string with the path to the value on that line, such as
+/// `root.config.timeout: `. Struct fields are written as `.field`, tuple fields as `.0`, and list
+/// items, map keys, and set items in brackets. The prefixes are padded with spaces to the same
+/// width, measured with `display_width`.
+pub(crate) fn breadcrumbs<F>(debug_str: &str, display_width: F) -> Vec<String>
+where
+    F: Fn(&str) -> usize,
+{
+    // Paths of the blocks that contain the current line, from the outermost
+    let mut blocks: Vec<PathBlock> = Vec::new();
+    let mut paths = Vec::new();
+    for line in debug_str.lines() {
+        let content = line.trim_start_matches(' ');
+        let indent = line.len() - content.len();
+
+        let is_closing = blocks.last().map_or(false, |block| {
+            indent == block.indent && content.starts_with([']', '}', ')'])
+        });
+        let path = if is_closing {
+            blocks.pop().unwrap().path
+        } else if let Some(block) = blocks.last_mut() {
+            block.item_path(content)
+        } else {
+            "root".to_string()
+        };
+
+        if content.ends_with(['[', '{', '(']) {
+            blocks.push(PathBlock {
+                indent,
+                kind: PathKind::of(content),
+                path: path.clone(),
+                item_count: 0,
+            });
+        }
+        paths.push(format!("{}:", path));
+    }
+
+    let width = paths
+        .iter()
+        .map(|path| display_width(path))
+        .max()
+        .unwrap_or(0);
+    for path in &mut paths {
+        let padding = width - display_width(path) + 1;
+        path.extend(repeat(' ').take(padding));
+    }
+    paths
+}
+
+/// A block of [`breadcrumbs()`].
+struct PathBlock {
+    indent: usize,
+    kind: PathKind,
+    path: String,
+    item_count: usize,
+}
+
+impl PathBlock {
+    /// Path of the item on the line `content`, which is directly inside this block.
+    fn item_path(&mut self, content: &str) -> String {
+        let index = self.item_count;
+        self.item_count += 1;
+        let key = split_key(content);
+        match (self.kind, key) {
+            (PathKind::Struct, Some(field)) => format!("{}.{}", self.path, field),
+            (PathKind::Tuple, _) => format!("{}.{}", self.path, index),
+            (PathKind::Map, Some(key)) => format!("{}[{}]", self.path, key),
+            _ => format!("{}[{}]", self.path, index),
+        }
+    }
+}
+
+/// Key or field name of the item on the line `content`, which is followed by `: `. A quoted key
+/// may contain `: ` itself.
+fn split_key(content: &str) -> Option<&str> {
+    let key_end = match content.chars().next() {
+        Some(quote) if quote == '"' || quote == '\'' => {
+            // End of the quoted key, skipping escaped characters
+            let mut escaped = false;
+            let (end, _) = content.char_indices().skip(1).find(|&(_, c)| {
+                let is_end = c == quote && !escaped;
+                escaped = c == '\\' && !escaped;
+                is_end
+            })?;
+            end + 1
+        }
+        _ => content.find(": ")?,
+    };
+    if content[key_end..].starts_with(": ") {
+        Some(&content[..key_end])
+    } else {
+        None
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum PathKind {
+    Struct,
+    Tuple,
+    List,
+    /// A map or a set. Items with keys are map entries.
+    Map,
+}
+
+impl PathKind {
+    /// Kind of the block opened by the line `content`.
+    fn of(content: &str) -> Self {
+        if content.ends_with('[') {
+            PathKind::List
+        } else if content.ends_with('(') {
+            PathKind::Tuple
+        } else if content == "{" || content.ends_with(": {") {
+            PathKind::Map
+        } else {
+            PathKind::Struct
+        }
+    }
+}
+
+/// A part of a `{:#?}` string between an opening bracket and its closing bracket.
+struct Block {
+    indent: usize,
+    /// True for lists, sets, and maps; false for structs and tuples.
+    is_collection: bool,
+    /// Number of items seen so far.
+    item_count: usize,
+}
+
+impl Block {
+    /// Number of items seen so far that are left out.
+    fn hidden_count(&self, max_items: usize) -> usize {
+        if self.is_collection {
+            self.item_count.saturating_sub(max_items)
+        } else {
+            0
+        }
+    }
+}
+
+/// Returns true if the current line is part of an item that is left out.
+fn is_hidden(blocks: &[Block], max_items: usize) -> bool {
+    blocks.iter().any(|block| block.hidden_count(max_items) > 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn summarize_vec() {
+        let debug_str = format!("{:#?}", (0..100).collect::<Vec<i32>>());
+        assert_eq!(
+            summarize_collections(&debug_str, 3),
+            "[\n    0,\n    1,\n    2,\n    .. 97 more items ..\n]\n"
+        );
+        assert_eq!(
+            summarize_collections(&debug_str, 100),
+            format!("{}\n", debug_str)
+        );
+        assert_eq!(
+            summarize_collections(&format!("{:#?}", [0, 1, 2, 3]), 3),
+            "[\n    0,\n    1,\n    2,\n    .. 1 more item ..\n]\n"
+        );
+    }
+
+    #[test]
+    fn sort_collections() {
+        #[derive(Debug)]
+        #[allow(dead_code)]
+        struct Point {
+            y: i32,
+            x: i32,
+        }
+
+        let debug_str = r#"{
+    "b": [
+        3,
+        1,
+    ],
+    "a": Point {
+        y: 2,
+        x: 1,
+    },
+    "c": {
+        2,
+        1,
+    },
+}"#;
+        assert_eq!(
+            super::sort_collections(debug_str),
+            r#"{
+    "a": Point {
+        y: 2,
+        x: 1,
+    },
+    "b": [
+        3,
+        1,
+    ],
+    "c": {
+        1,
+        2,
+    },
+}
+"#
+        );
+        assert_eq!(super::sort_collections("1"), "1\n");
+        assert_eq!(super::sort_collections(""), "");
+    }
+
+    #[test]
+    fn breadcrumbs() {
+        #[derive(Debug)]
+        #[allow(dead_code)]
+        struct Config {
+            timeout: u32,
+            hosts: Vec<&'static str>,
+        }
+
+        #[derive(Debug)]
+        #[allow(dead_code)]
+        struct Root {
+            config: Config,
+            pair: (u8, u8),
+            ports: BTreeMap<&'static str, u16>,
+        }
+
+        let root = Root {
+            config: Config {
+                timeout: 30,
+                hosts: vec!["a"],
+            },
+            pair: (1, 2),
+            ports: [("http", 80), ("a: b", 1)].into(),
+        };
+        let with_breadcrumbs = |debug_str: &str| -> String {
+            let prefixes = super::breadcrumbs(debug_str, |s| s.chars().count());
+            prefixes
+                .iter()
+                .zip(debug_str.lines())
+                .map(|(prefix, line)| format!("{}{}\n", prefix, line))
+                .collect()
+        };
+        let result = with_breadcrumbs(&format!("{:#?}", root));
+        let expected = r#"root:                 Root {
+root.config:              config: Config {
+root.config.timeout:          timeout: 30,
+root.config.hosts:            hosts: [
+root.config.hosts[0]:             "a",
+root.config.hosts:            ],
+root.config:              },
+root.pair:                pair: (
+root.pair.0:                  1,
+root.pair.1:                  2,
+root.pair:                ),
+root.ports:               ports: {
+root.ports["a: b"]:           "a: b": 1,
+root.ports["http"]:           "http": 80,
+root.ports:               },
+root:                 }
+"#;
+        assert_eq!(result, expected);
+        assert_eq!(with_breadcrumbs("1"), "root: 1\n");
+    }
+
+    #[rstest]
+    #[case("timeout: 30,", Some("timeout"))]
+    #[case("name: \"a: b\",", Some("name"))]
+    #[case("\"a: b\": 1,", Some("\"a: b\""))]
+    #[case("\"a\\\": b\": 1,", Some("\"a\\\": b\""))]
+    #[case("':': 2,", Some("':'"))]
+    #[case("\"a: b\",", None)]
+    #[case("1,", None)]
+    fn split_key(#[case] content: &str, #[case] expected: Option<&str>) {
+        assert_eq!(super::split_key(content), expected);
+    }
+
+    #[test]
+    fn summarize_nested() {
+        #[derive(Debug)]
+        #[allow(dead_code)]
+        struct Point {
+            x: i32,
+            y: i32,
+            z: i32,
+        }
+
+        let points: Vec<Point> = (0..4).map(|i| Point { x: i, y: i, z: i }).collect();
+        let mut map = BTreeMap::new();
+        map.insert("points", points);
+        map.insert("empty", Vec::new());
+        let result = summarize_collections(&format!("{:#?}", map), 1);
+        assert_eq!(
+            result,
+            r#"{
+    "empty": [],
+    .. 1 more item ..
+}
+"#
+        );
+
+        let map: BTreeMap<&str, Vec<Point>> = [(
+            "points",
+            (0..4).map(|i| Point { x: i, y: i, z: i }).collect(),
+        )]
+        .into();
+        let result = summarize_collections(&format!("{:#?}", map), 2);
+        assert_eq!(
+            result,
+            r#"{
+    "points": [
+        Point {
+            x: 0,
+            y: 0,
+            z: 0,
+        },
+        Point {
+            x: 1,
+            y: 1,
+            z: 1,
+        },
+        .. 2 more items ..
+    ],
+}
+"#
+        );
+    }
+}
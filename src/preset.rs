@@ -0,0 +1,207 @@
+//! A small format for sharing decoration settings. See [`PrettierPrinter::from_preset_str()`].
+//!
+//! Each line is `key=value`. Blank lines and lines starting with `#` are ignored. The keys are:
+//!
+//! - `border`: character at the corners of the box
+//! - `stars`: characters to choose stars from
+//! - `weights`: comma-separated weight of each star; defaults to equal weights
+//! - `ratio`: chance that a line gets a star, such as `3/5`
+//! - `color`: `true`, `false`, or `auto`
+//! - `emoji_width`: `1` or `2`
+//! - `rainbow_text`: `true` or `false`
+//!
+//! ```text
+//! border=🌈
+//! stars=⭐🌟
+//! ratio=3/5
+//! color=true
+//! ```
+
+use crate::prettier_printer::{ColorChoice, PaletteError, PrettierPrinter};
+use std::fmt::{Display, Formatter};
+
+/// Error returned by [`PrettierPrinter::from_preset_str()`]. Line numbers start from 1.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PresetError {
+    /// The line does not contain `=`.
+    MissingEquals { line: usize },
+    /// The key is not one of the known keys.
+    UnknownKey { line: usize, key: String },
+    /// The value cannot be used for the key.
+    InvalidValue {
+        line: usize,
+        key: String,
+        value: String,
+    },
+    /// The stars and weights cannot be used together.
+    Palette(PaletteError),
+}
+
+impl Display for PresetError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PresetError::MissingEquals { line } => write!(f, "line {}: expected key=value", line),
+            PresetError::UnknownKey { line, key } => {
+                write!(f, "line {}: unknown key \"{}\"", line, key)
+            }
+            PresetError::InvalidValue { line, key, value } => write!(
+                f,
+                "line {}: invalid value \"{}\" for \"{}\"",
+                line, value, key
+            ),
+            PresetError::Palette(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for PresetError {}
+
+impl From<PaletteError> for PresetError {
+    fn from(e: PaletteError) -> Self {
+        PresetError::Palette(e)
+    }
+}
+
+/// Applies the settings in `preset` to `printer`.
+pub(crate) fn apply(
+    mut printer: PrettierPrinter,
+    preset: &str,
+) -> Result<PrettierPrinter, PresetError> {
+    let mut stars: Option<Vec<char>> = None;
+    let mut weights: Option<Vec<u16>> = None;
+
+    for (i, line) in preset.lines().enumerate() {
+        let line_number = i + 1;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let (key, value) = line
+            .split_once('=')
+            .ok_or(PresetError::MissingEquals { line: line_number })?;
+        let (key, value) = (key.trim(), value.trim());
+        let invalid = || PresetError::InvalidValue {
+            line: line_number,
+            key: key.to_string(),
+            value: value.to_string(),
+        };
+
+        match key {
+            "border" => {
+                let mut chars = value.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(border), None) => printer = printer.with_border(border),
+                    _ => return Err(invalid()),
+                }
+            }
+            "stars" => stars = Some(value.chars().collect()),
+            "weights" => {
+                let parsed: Result<Vec<u16>, _> =
+                    value.split(',').map(|n| n.trim().parse()).collect();
+                weights = Some(parsed.map_err(|_| invalid())?);
+            }
+            "ratio" => {
+                let (numerator, denominator) = value
+                    .split_once('/')
+                    .and_then(|(n, d)| Some((n.trim().parse().ok()?, d.trim().parse().ok()?)))
+                    .filter(|&(n, d): &(u32, u32)| d > 0 && n <= d)
+                    .ok_or_else(invalid)?;
+                printer = printer.with_sparkle_ratio(numerator, denominator);
+            }
+            "color" => {
+                let color = match value {
+                    "true" => ColorChoice::Always,
+                    "false" => ColorChoice::Never,
+                    "auto" => ColorChoice::Auto,
+                    _ => return Err(invalid()),
+                };
+                printer = printer.with_color(color);
+            }
+            "emoji_width" => match value {
+                "1" => printer = printer.with_emoji_width(1),
+                "2" => printer = printer.with_emoji_width(2),
+                _ => return Err(invalid()),
+            },
+            "rainbow_text" => {
+                printer = printer.with_rainbow_text(value.parse().map_err(|_| invalid())?)
+            }
+            _ => {
+                return Err(PresetError::UnknownKey {
+                    line: line_number,
+                    key: key.to_string(),
+                })
+            }
+        }
+    }
+
+    match (stars, weights) {
+        (Some(stars), Some(weights)) => printer = printer.try_with_palette(&stars, &weights)?,
+        (Some(stars), None) => printer = printer.try_with_palette(&stars, &vec![1; stars.len()])?,
+        (None, Some(weights)) => {
+            let stars = printer.config().stars.clone();
+            printer = printer.try_with_palette(&stars, &weights)?;
+        }
+        (None, None) => {}
+    }
+    Ok(printer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[test]
+    fn from_preset_str() {
+        let preset = "\
+# Shared look
+border=🎀
+stars=⭐🌟
+weights=3, 1
+
+ratio=1/2
+color=auto
+emoji_width=1
+rainbow_text=true
+";
+        let printer = PrettierPrinter::from_preset_str(preset).unwrap();
+        let config = printer.config();
+        assert_eq!(config.border, '🎀');
+        assert_eq!(config.stars, ['⭐', '🌟']);
+        assert_eq!(config.weights, [3, 1]);
+        assert_eq!(config.sparkle_ratio, (1, 2));
+        assert_eq!(config.color, ColorChoice::Auto);
+        assert_eq!(config.emoji_width, 1);
+        assert!(config.rainbow_text);
+
+        let config = PrettierPrinter::from_preset_str("stars=ab")
+            .unwrap()
+            .config()
+            .clone();
+        assert_eq!(config.weights, [1, 1]);
+    }
+
+    #[rstest]
+    #[case("border", PresetError::MissingEquals { line: 1 })]
+    #[case("\nsize=3", PresetError::UnknownKey { line: 2, key: "size".to_string() })]
+    #[case(
+        "border=ab",
+        PresetError::InvalidValue { line: 1, key: "border".to_string(), value: "ab".to_string() }
+    )]
+    #[case(
+        "ratio=5/3",
+        PresetError::InvalidValue { line: 1, key: "ratio".to_string(), value: "5/3".to_string() }
+    )]
+    #[case(
+        "color=yes",
+        PresetError::InvalidValue { line: 1, key: "color".to_string(), value: "yes".to_string() }
+    )]
+    #[case("weights=0,0,0,0", PresetError::Palette(PaletteError::AllZero))]
+    fn preset_error(#[case] preset: &str, #[case] expected: PresetError) {
+        assert_eq!(
+            PrettierPrinter::from_preset_str(preset).unwrap_err(),
+            expected
+        );
+    }
+}
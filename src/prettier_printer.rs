@@ -1,9 +1,29 @@
+use crate::color;
+use crate::diff::{self, DiffLine};
+use crate::layout;
+use crate::outline;
+use crate::preset::{self, PresetError};
+use crate::width::{self, display_width, is_printable_ascii, safe_truncate};
+use crossterm::cursor::MoveTo;
+use crossterm::queue;
+use crossterm::style::{Color, Print};
+use crossterm::tty::IsTty;
 use rand::distributions::{Bernoulli, Distribution};
 use rand::rngs::SmallRng;
 use rand::{Rng, SeedableRng};
-use rand_distr::WeightedAliasIndex;
+use rand_distr::{WeightedAliasIndex, WeightedError};
+#[cfg(feature = "rng-trace")]
+use std::collections::VecDeque;
+use std::convert::{Infallible, TryFrom};
 use std::fmt::{Debug, Display, Formatter};
-use std::iter::repeat;
+use std::io;
+use std::io::{BufRead, Seek, SeekFrom};
+use std::iter::{once, repeat};
+use std::mem::take;
+use std::ops::Range;
+use std::sync::Arc;
+use std::time::Instant;
+use unicode_segmentation::UnicodeSegmentation;
 
 pub type Seed = <SmallRng as SeedableRng>::Seed;
 
@@ -11,6 +31,7 @@ pub type Seed = <SmallRng as SeedableRng>::Seed;
 #[derive(Debug, Clone)]
 pub struct PrettierPrinter {
     rng: SmallRng,
+    config: PrettierConfig,
 }
 
 impl PrettierPrinter {
@@ -18,9 +39,335 @@ impl PrettierPrinter {
     pub fn new_with_seed(seed: Seed) -> Self {
         Self {
             rng: SmallRng::from_seed(seed),
+            config: PrettierConfig::default(),
         }
     }
 
+    /// Instantiates `PrettierPrinter` with a seed derived from `label`, so that the same label
+    /// always produces the same output. See [`seed_from_bytes()`] for how the seed is derived.
+    pub fn from_label(label: &str) -> Self {
+        Self::new_with_seed(seed_from_bytes(label.as_bytes()))
+    }
+
+    /// Creates a printer with the settings in `preset`. See [`crate::preset`] for the format.
+    pub fn from_preset_str(preset: &str) -> Result<Self, PresetError> {
+        preset::apply(PrettierPrinter::default(), preset)
+    }
+
+    /// Current settings.
+    pub fn config(&self) -> &PrettierConfig {
+        &self.config
+    }
+
+    /// Replaces every setting with `config`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if [`PrettierPrinter::try_with_config()`] would return an error.
+    pub fn with_config(self, config: PrettierConfig) -> Self {
+        self.try_with_config(config)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Like [`PrettierPrinter::with_config()`], but returns an error if a field has a value that
+    /// the `with_*` method of the field would not accept.
+    pub fn try_with_config(mut self, config: PrettierConfig) -> Result<Self, ConfigError> {
+        config.validate()?;
+        self.config = config;
+        Ok(self)
+    }
+
+    /// Sets where stars are placed. Defaults to [`StarLocation::Interior`].
+    pub fn with_star_location(mut self, star_location: StarLocation) -> Self {
+        self.config.star_location = star_location;
+        self
+    }
+
+    /// Decorates each line with its own seed instead of the printer's seed. The seeds are reused
+    /// from the start if there are more lines than seeds. Pass an empty `Vec` to go back to using
+    /// the printer's seed.
+    pub fn with_line_seeds(mut self, line_seeds: Vec<Seed>) -> Self {
+        self.config.line_seeds = line_seeds;
+        self
+    }
+
+    /// Colors the content with a rainbow gradient that changes hue with each column.
+    pub fn with_rainbow_text(mut self, rainbow_text: bool) -> Self {
+        self.config.rainbow_text = rainbow_text;
+        self
+    }
+
+    /// Sets the character at the corners of the box. Defaults to [`DEFAULT_BORDER`].
+    pub fn with_border(mut self, border: char) -> Self {
+        self.config.border = border;
+        self
+    }
+
+    /// Sets the stars to choose from, and how often each star is chosen relative to the others.
+    /// Defaults to [`DEFAULT_STARS`] and [`DEFAULT_WEIGHTS`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if [`PrettierPrinter::try_with_palette()`] would return an error.
+    pub fn with_palette(self, stars: &[char], weights: &[u16]) -> Self {
+        self.try_with_palette(stars, weights)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Like [`PrettierPrinter::with_palette()`], but returns an error if `stars` and `weights`
+    /// have different lengths, or if the weights cannot be used to choose a star.
+    pub fn try_with_palette(
+        mut self,
+        stars: &[char],
+        weights: &[u16],
+    ) -> Result<Self, PaletteError> {
+        check_palette(stars, weights)?;
+        self.config.stars = stars.to_vec();
+        self.config.weights = weights.to_vec();
+        Ok(self)
+    }
+
+    /// Sets the number of bytes in each row of [`PrettierPrinter::print_bytes()`]. Defaults to
+    /// 16.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bytes_per_row` is zero.
+    pub fn with_bytes_per_row(mut self, bytes_per_row: usize) -> Self {
+        assert_ne!(bytes_per_row, 0, "bytes_per_row must be non-zero");
+        self.config.bytes_per_row = bytes_per_row;
+        self
+    }
+
+    /// Sets whether colors are written. Defaults to [`ColorChoice::Always`]. No colors are
+    /// written unless a colorful option such as [`PrettierPrinter::with_rainbow_text()`] is
+    /// enabled.
+    pub fn with_color(mut self, color: ColorChoice) -> Self {
+        self.config.color = color;
+        self
+    }
+
+    /// Instead of adding at most one star after each line, adds a star to each column after the
+    /// line with probability `density`, so that shorter lines get more stars.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `density` is not between 0 and 1.
+    pub fn with_density_per_column(mut self, density: f64) -> Self {
+        assert!(
+            (0.0..=1.0).contains(&density),
+            "density must be between 0 and 1; got {}",
+            density
+        );
+        self.config.density_per_column = Some(density);
+        self
+    }
+
+    /// Splits the content into `column_count` panels placed side by side, like newspaper
+    /// columns, so that tall values take up less of the screen. Defaults to 1.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `column_count` is zero.
+    pub fn with_column_split(mut self, column_count: usize) -> Self {
+        assert_ne!(column_count, 0, "column_count must be non-zero");
+        self.config.column_split = column_count;
+        self
+    }
+
+    /// Places about `stars_per_100_cells` stars per 100 cells of the box instead of deciding
+    /// line by line, so that small and large boxes are equally sparkly. Stars go after the
+    /// content as with [`PrettierPrinter::with_density_per_column()`], which this overrides.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `stars_per_100_cells` is negative.
+    pub fn with_star_density(mut self, stars_per_100_cells: f64) -> Self {
+        assert!(
+            stars_per_100_cells >= 0.0,
+            "stars_per_100_cells must not be negative; got {}",
+            stars_per_100_cells
+        );
+        self.config.star_density = Some(stars_per_100_cells);
+        self
+    }
+
+    /// Stops placing stars once `max_total_stars` stars have been placed in the box, including
+    /// the border. Defaults to no limit.
+    pub fn with_max_total_stars(mut self, max_total_stars: usize) -> Self {
+        self.config.max_total_stars = Some(max_total_stars);
+        self
+    }
+
+    /// Sets the character that fills the space around stars after the content and on the
+    /// borders. It should be one column wide. Fill after the last star of a line is removed.
+    /// Defaults to a space.
+    pub fn with_fill(mut self, fill: char) -> Self {
+        self.config.fill = fill;
+        self
+    }
+
+    /// Prefixes every line of the box, including the borders, with `indent` spaces. Defaults to
+    /// 0.
+    pub fn with_indent(mut self, indent: usize) -> Self {
+        self.config.indent = indent;
+        self
+    }
+
+    /// Shows only the first `max_items` items of each list, set, and map in the `{:#?}` string,
+    /// and replaces the rest with a line such as `.. 97 more items ..`. Defaults to showing every
+    /// item.
+    pub fn with_collection_summary(mut self, max_items: usize) -> Self {
+        self.config.collection_summary = Some(max_items);
+        self
+    }
+
+    /// Draws the edges of the box with box-drawing characters that are tinted with a rainbow
+    /// gradient. The corners are still drawn with the border character.
+    pub fn with_frame_rainbow(mut self, frame_rainbow: bool) -> Self {
+        self.config.frame_rainbow = frame_rainbow;
+        self
+    }
+
+    /// Draws a shadow one column to the right of and one row below the box.
+    pub fn with_shadow(mut self, shadow: bool) -> Self {
+        self.config.shadow = shadow;
+        self
+    }
+
+    /// Applies `transform` to each line of the debug string before it is decorated, for example
+    /// to mask secrets. The box is sized to fit the transformed lines.
+    pub fn with_line_transform<F>(mut self, transform: F) -> Self
+    where
+        F: Fn(&str) -> String + Send + Sync + 'static,
+    {
+        self.config.line_transform = Some(LineTransform::new(transform));
+        self
+    }
+
+    /// Replaces the value of each struct field or string map key named in `fields` with `****`,
+    /// so that secrets are not printed. This happens before the
+    /// [`PrettierPrinter::with_line_transform()`] transform.
+    pub fn with_redact(mut self, fields: Vec<String>) -> Self {
+        self.config.redact = fields;
+        self
+    }
+
+    /// Keeps `left` columns between the left edge of the box and the content, and `right` columns
+    /// between the content and the right edge. The box is widened by `left + right` columns, and
+    /// no stars are placed in the right margin. Defaults to no margins.
+    pub fn with_interior_margin(mut self, left: usize, right: usize) -> Self {
+        self.config.interior_margin = (left, right);
+        self
+    }
+
+    /// If the debug string is already framed, adds stars to its top and bottom edges instead of
+    /// drawing another box around it. A frame is a first and last line that start and end with the
+    /// same character, such as `+---+` or `┌───┐`/`└───┘`, with only box-drawing characters,
+    /// `-`, `=`, and spaces in between. Other settings do not apply to framed strings.
+    pub fn with_detect_existing_border(mut self, detect_existing_border: bool) -> Self {
+        self.config.detect_existing_border = detect_existing_border;
+        self
+    }
+
+    /// Measures the number of columns each character takes up with `width_fn` instead of the
+    /// Unicode width, for terminals that disagree about emojis and characters of ambiguous width.
+    /// [`PrettierPrinter::with_emoji_width()`] is not used when this is set; the stars are assumed
+    /// to be as wide as the border.
+    pub fn with_width_fn<F>(mut self, width_fn: F) -> Self
+    where
+        F: Fn(char) -> usize + Send + Sync + 'static,
+    {
+        self.config.width_fn = Some(WidthFn::new(width_fn));
+        self
+    }
+
+    /// Prefixes each line with the path to its value, such as `root.config.timeout:`, to find
+    /// values in a large debug string. The prefix is dim when colors are written, and is not
+    /// changed by [`PrettierPrinter::with_rainbow_text()`] or
+    /// [`PrettierPrinter::with_line_transform()`], or cut off by a width hint.
+    pub fn with_breadcrumbs(mut self, breadcrumbs: bool) -> Self {
+        self.config.breadcrumbs = breadcrumbs;
+        self
+    }
+
+    /// Sets how the output is laid out. Defaults to [`Style::Box`].
+    pub fn with_style(mut self, style: Style) -> Self {
+        self.config.style = style;
+        self
+    }
+
+    /// Scales the brightness of the colored frame and text, from 0.0 for no colors to 1.0 for
+    /// full brightness. Defaults to 1.0.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `intensity` is not between 0.0 and 1.0.
+    pub fn with_intensity(mut self, intensity: f32) -> Self {
+        assert!(
+            (0.0..=1.0).contains(&intensity),
+            "invalid intensity {}",
+            intensity
+        );
+        self.config.intensity = intensity;
+        self
+    }
+
+    /// Sets the chance that a line gets a star to `numerator / denominator`. Defaults to 3/5.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `denominator` is zero or less than `numerator`.
+    pub fn with_sparkle_ratio(mut self, numerator: u32, denominator: u32) -> Self {
+        assert!(
+            denominator > 0 && numerator <= denominator,
+            "invalid ratio {}/{}",
+            numerator,
+            denominator
+        );
+        self.config.sparkle_ratio = (numerator, denominator);
+        self
+    }
+
+    /// Mirrors the decorated box. Defaults to [`Flip::None`].
+    pub fn with_flip(mut self, flip: Flip) -> Self {
+        self.config.flip = flip;
+        self
+    }
+
+    /// Decorates each line with its own seed, derived from the printer's seed and the line
+    /// number with [`seed_from_bytes()`], so that neighboring lines are not decorated alike. The
+    /// output is still the same for the same seed, but differs from the output without this
+    /// option. [`PrettierPrinter::with_line_seeds()`] takes precedence over this.
+    pub fn with_decorrelated_lines(mut self, decorrelated_lines: bool) -> Self {
+        self.config.decorrelated_lines = decorrelated_lines;
+        self
+    }
+
+    /// Sets how many columns an emoji takes up in your terminal. Must be 1 or 2. Defaults to 2.
+    /// Once this is set, a star in the indentation takes the place of as many spaces as it is
+    /// wide, so that the content stays in its column. Otherwise each star takes the place of one
+    /// space.
+    ///
+    /// # Panics
+    ///
+    /// Panics if [`PrettierPrinter::try_with_emoji_width()`] would return an error.
+    pub fn with_emoji_width(self, emoji_width: usize) -> Self {
+        self.try_with_emoji_width(emoji_width)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Like [`PrettierPrinter::with_emoji_width()`], but returns an error if `emoji_width` is not
+    /// 1 or 2.
+    pub fn try_with_emoji_width(mut self, emoji_width: usize) -> Result<Self, EmojiWidthError> {
+        if !matches!(emoji_width, 1 | 2) {
+            return Err(EmojiWidthError { emoji_width });
+        }
+        self.config.emoji_width = emoji_width;
+        self.config.sized_stars = true;
+        Ok(self)
+    }
+
     /// Generates a `Seed` from given `SmallRng`.
     pub fn gen_seed(rng: &mut SmallRng) -> Seed {
         let mut seed = Seed::default();
@@ -28,12 +375,252 @@ impl PrettierPrinter {
         seed
     }
 
+    /// Combines two seeds into a new one, for deriving a seed for "this value in this context".
+    /// The result is [`seed_from_bytes()`] of `a` followed by `b`, so it is stable across
+    /// platforms and the order matters: `mix_seeds(a, b)` is usually not `mix_seeds(b, a)`.
+    pub fn mix_seeds(a: Seed, b: Seed) -> Seed {
+        let mut bytes = [0; 64];
+        bytes[..32].copy_from_slice(&a);
+        bytes[32..].copy_from_slice(&b);
+        seed_from_bytes(&bytes)
+    }
+
     /// Pass your variable to this.
+    ///
+    /// The box is sized by the number of terminal columns each line takes up, so wide characters
+    /// such as `日` count as two columns, and emojis as [`PrettierPrinter::with_emoji_width()`].
     pub fn print<'a, T>(&mut self, inner: &'a T) -> PrettierPrintDisplayer<'a, T> {
         PrettierPrintDisplayer {
             seed: PrettierPrinter::gen_seed(&mut self.rng),
             inner,
+            config: self.config.clone(),
+        }
+    }
+
+    /// Like [`PrettierPrinter::print()`], but sizes the box for content that is `width` columns
+    /// wide instead of measuring the content, which saves a pass over it when you already know
+    /// the width. Lines that are wider than `width` are truncated.
+    pub fn print_with_width_hint<'a, T>(
+        &mut self,
+        inner: &'a T,
+        width: usize,
+    ) -> PrettierPrintDisplayer<'a, T> {
+        let mut displayer = self.print(inner);
+        displayer.config.width_hint = Some(width);
+        displayer
+    }
+
+    /// Prints rows of values as a grid with aligned columns. Each cell is formatted with `{:?}`
+    /// and right-aligned to the width of the widest cell in its column. Missing cells in short
+    /// rows are left blank.
+    pub fn print_grid<T>(&mut self, rows: &[Vec<T>]) -> PrettierPrintText
+    where
+        T: Debug,
+    {
+        self.print_text(grid_string(rows))
+    }
+
+    /// Prints the entries of a map with the keys in a column padded to the longest key, followed
+    /// by the values. Keys and values are formatted with `{:?}`. If `sorted` is true, the
+    /// entries are sorted by their key strings, which makes the output of a `HashMap`
+    /// deterministic.
+    pub fn print_map<I, K, V>(&mut self, map: I, sorted: bool) -> PrettierPrintText
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: Debug,
+        V: Debug,
+    {
+        self.print_text(map_string(map, sorted))
+    }
+
+    /// Prints fields of a structured log record as `key = value` lines, with the keys padded on
+    /// the left so that the `=` signs line up. Values are formatted with `{:?}`.
+    pub fn print_kv(&mut self, pairs: &[(&str, &dyn Debug)]) -> PrettierPrintText {
+        let text = kv_string(pairs, |s| self.config.display_width(s));
+        self.print_text(text)
+    }
+
+    /// Like [`PrettierPrinter::print()`], but adds a footer line with how long it took to format
+    /// the `Debug` string.
+    pub fn print_timed<T>(&mut self, inner: &T) -> PrettierPrintText
+    where
+        T: Debug,
+    {
+        self.print_timed_with_clock(inner, Instant::now)
+    }
+
+    fn print_timed_with_clock<T, C>(&mut self, inner: &T, mut clock: C) -> PrettierPrintText
+    where
+        T: Debug,
+        C: FnMut() -> Instant,
+    {
+        let start = clock();
+        let mut text = format!("{:#?}", inner);
+        let elapsed = clock().saturating_duration_since(start);
+        text.push_str(&format!("\n{} {:?}", TIMED_FOOTER, elapsed));
+        self.print_text(text)
+    }
+
+    /// Prints a hex dump of `bytes`. Each row shows the offset, the bytes in hex, and the bytes
+    /// as ASCII with non-printable bytes replaced by `.`. See also
+    /// [`PrettierPrinter::with_bytes_per_row()`].
+    pub fn print_bytes(&mut self, bytes: &[u8]) -> PrettierPrintText {
+        let dump = hex_dump(bytes, self.config.bytes_per_row);
+        self.print_text(dump)
+    }
+
+    /// Like [`PrettierPrinter::print()`], but uses the `Display` string instead of the `Debug`
+    /// string.
+    pub fn print_display<T>(&mut self, inner: &T) -> PrettierPrintText
+    where
+        T: Display,
+    {
+        self.print_text(inner.to_string())
+    }
+
+    /// Like [`PrettierPrinter::print()`], but sorts the entries of every map and set by their
+    /// `{:#?}` strings, so that the output of a `HashMap` or `HashSet` is the same on every run.
+    /// Lists, structs, and tuples keep their order.
+    pub fn print_sorted<T>(&mut self, inner: &T) -> PrettierPrintText
+    where
+        T: Debug,
+    {
+        let mut sorted = outline::sort_collections(&format!("{:#?}", inner));
+        sorted.pop(); // Remove the last line break
+        self.print_text(sorted)
+    }
+
+    /// Prints the line-level difference between the `{:#?}` strings of `before` and `after`.
+    /// Added lines are marked with [`DIFF_ADDED`] and removed lines with [`DIFF_REMOVED`].
+    pub fn print_diff<T>(&mut self, before: &T, after: &T) -> PrettierPrintText
+    where
+        T: Debug,
+    {
+        let before = format!("{:#?}", before);
+        let after = format!("{:#?}", after);
+        let unchanged = " ".repeat(self.config.emoji_width + 1);
+
+        let mut text = String::new();
+        for line in diff::diff_lines(&before, &after) {
+            let (marker, line) = match line {
+                DiffLine::Unchanged(line) => (unchanged.as_str(), line),
+                DiffLine::Added(line) => (DIFF_ADDED, line),
+                DiffLine::Removed(line) => (DIFF_REMOVED, line),
+            };
+            text.push_str(marker);
+            text.push_str(line);
+            text.push('\n');
         }
+        self.print_text(text)
+    }
+
+    /// Reads lines from `reader` and writes the decorated box to `writer` as it goes, without
+    /// holding the whole text in memory. The box is sized for lines that are at most
+    /// `content_width` columns wide; longer lines stick out of the box. With
+    /// [`ColorChoice::Auto`], colors are not written, because `writer` might not be a terminal.
+    /// [`PrettierPrinter::with_column_split()`] is not applied.
+    ///
+    /// See [`PrettierPrinter::decorate_reader_two_pass()`] to size the box to the text instead.
+    pub fn decorate_reader<R, W>(
+        &mut self,
+        reader: R,
+        mut writer: W,
+        content_width: usize,
+    ) -> io::Result<()>
+    where
+        R: BufRead,
+        W: io::Write,
+    {
+        let seed = PrettierPrinter::gen_seed(&mut self.rng);
+        let color = self.config.color.enabled_unknown();
+        self.config.render_lines(
+            seed,
+            reader.lines(),
+            box_width(content_width),
+            color,
+            &mut Trace::default(),
+            |s| writer.write_all(s.as_bytes()),
+        )
+    }
+
+    /// Like [`PrettierPrinter::decorate_reader()`], but reads `reader` twice: once to measure the
+    /// longest line, and again from the same position to write the box.
+    pub fn decorate_reader_two_pass<R, W>(&mut self, mut reader: R, writer: W) -> io::Result<()>
+    where
+        R: BufRead + Seek,
+        W: io::Write,
+    {
+        let start = reader.stream_position()?;
+        let mut content_width = 0;
+        for line in reader.by_ref().lines() {
+            content_width = content_width.max(self.config.display_width(&line?));
+        }
+        reader.seek(SeekFrom::Start(start))?;
+        self.decorate_reader(reader, writer, content_width)
+    }
+
+    /// Renders `inner` with each of `seeds` and arranges the boxes in a grid with `columns`
+    /// columns, for picking the seed you like best. Each box is labeled with the first bytes of
+    /// its seed in hex.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `columns` is zero.
+    pub fn contact_sheet<T>(&self, inner: &T, seeds: &[Seed], columns: usize) -> String
+    where
+        T: Debug,
+    {
+        assert_ne!(columns, 0, "columns must be non-zero");
+        let debug_str = format!("{:#?}", inner);
+        let cells: Vec<String> = seeds
+            .iter()
+            .map(|&seed| {
+                let label = format!(" {}", seed_label(seed));
+                layout::join_vertical(&[&self.config.output(seed, &debug_str), &label])
+            })
+            .collect();
+        let rows: Vec<String> = cells
+            .chunks(columns)
+            .map(|row| {
+                let row: Vec<&str> = row.iter().map(String::as_str).collect();
+                layout::join_horizontal(&row, CONTACT_SHEET_GAP, |s| self.config.display_width(s))
+            })
+            .collect();
+        let rows: Vec<&str> = rows.iter().map(String::as_str).collect();
+        layout::join_vertical(&rows)
+    }
+
+    fn print_text(&mut self, text: String) -> PrettierPrintText {
+        PrettierPrintText {
+            seed: PrettierPrinter::gen_seed(&mut self.rng),
+            text,
+            config: self.config.clone(),
+        }
+    }
+
+    /// Decides where stars go in a box for content that is `content_width` columns wide and
+    /// `height` lines tall, without looking at the content. Apply the plan to content with
+    /// [`PrettierPrinter::apply_plan()`] to redraw a changing value with stars that stay in place.
+    pub fn plan(&mut self, content_width: usize, height: usize) -> DecorationPlan {
+        let seed = PrettierPrinter::gen_seed(&mut self.rng);
+        self.config.plan(seed, content_width, height)
+    }
+
+    /// Decorates `debug_str` with the stars in `plan` instead of random ones. A star is left out if
+    /// its line is longer than the planned width. Other settings, such as the fill, indent, frame,
+    /// and colors, are used as in the `Display` output.
+    pub fn apply_plan(&self, plan: &DecorationPlan, debug_str: &str) -> String {
+        self.config.apply_plan(plan, debug_str)
+    }
+
+    /// Opens an interactive screen that shows `inner` decorated with different seeds. Press the
+    /// left and right arrow keys to cycle seeds and Enter to print the chosen seed. Returns `None`
+    /// if Esc or q was pressed.
+    pub fn gallery<T>(&mut self, inner: &T) -> std::io::Result<Option<Seed>>
+    where
+        T: Debug,
+    {
+        crate::gallery::run(&mut self.rng, &self.config, inner)
     }
 }
 
@@ -42,6 +629,7 @@ impl Default for PrettierPrinter {
     fn default() -> Self {
         Self {
             rng: SmallRng::from_entropy(),
+            config: PrettierConfig::default(),
         }
     }
 }
@@ -52,150 +640,3365 @@ impl Default for PrettierPrinter {
 pub struct PrettierPrintDisplayer<'a, T> {
     seed: Seed,
     inner: &'a T,
+    config: PrettierConfig,
 }
 
 impl<T> PrettierPrintDisplayer<'_, T> {
+    /// Decorates `debug_str` with the default configuration.
     pub fn output(seed: Seed, debug_str: &str) -> String {
-        const RAINBOW: char = '🌈';
-        const STARS: &[char] = &['⭐', '🌟', '☀', '🦀'];
-        let weights: Vec<u16> = vec![1500, 300, 100, 1];
+        PrettierConfig::default().output(seed, debug_str)
+    }
 
-        let mut rng = SmallRng::from_seed(seed);
-        let mut line_rng = Bernoulli::from_ratio(3, 5)
-            .unwrap() // Can be unwrap_unchecked() when API is stabilized
-            .sample_iter(SmallRng::from_seed(PrettierPrinter::gen_seed(&mut rng)));
+    /// Queues crossterm commands that draw the decorated `debug_str` with its top-left corner at
+    /// `origin`. Call `flush()` on `w` to execute the commands. Lines below row `u16::MAX - 1`,
+    /// the last row that crossterm can move to, are not drawn.
+    pub fn queue_decorated<W>(
+        w: &mut W,
+        seed: Seed,
+        debug_str: &str,
+        origin: (u16, u16),
+    ) -> crossterm::Result<()>
+    where
+        W: io::Write,
+    {
+        PrettierConfig::default().queue_decorated(w, seed, debug_str, origin)
+    }
 
-        let mut star_rng = WeightedAliasIndex::new(weights.to_vec())
-            .unwrap()
-            .sample_iter(SmallRng::from_seed(PrettierPrinter::gen_seed(&mut rng)));
+    /// Like [`PrettierPrintDisplayer::output()`], but also returns every random decision that was
+    /// made, in order. This is a tool for finding out why the stars went where they did. Pass
+    /// the decisions to [`PrettierPrintDisplayer::output_replay()`] to decorate with them again.
+    /// Requires the `rng-trace` feature.
+    #[cfg(feature = "rng-trace")]
+    pub fn output_trace(seed: Seed, debug_str: &str) -> (String, Vec<RngDraw>) {
+        let mut trace = Trace {
+            source: DrawSource::Record(Vec::new()),
+            ..Trace::default()
+        };
+        let output = PrettierConfig::default().output_from(seed, debug_str, &mut trace);
+        match trace.source {
+            DrawSource::Record(draws) => (output, draws),
+            _ => unreachable!(),
+        }
+    }
 
-        let width = debug_str
-            .lines()
-            .map(|s| s.len())
-            .max()
-            .map_or(0, |n| n + n / 10 + 2);
+    /// Decorates `debug_str` with the decisions from [`PrettierPrintDisplayer::output_trace()`]
+    /// instead of random ones. The decisions can be edited to see how each affects the output.
+    /// Requires the `rng-trace` feature.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `draws` runs out or has a decision of the wrong kind, such as when `debug_str`
+    /// is not the string that was traced, or if a [`RngDraw::Star`] index is not in the palette.
+    #[cfg(feature = "rng-trace")]
+    pub fn output_replay(draws: &[RngDraw], debug_str: &str) -> String {
+        let mut trace = Trace {
+            source: DrawSource::Replay(draws.iter().copied().collect()),
+            ..Trace::default()
+        };
+        PrettierConfig::default().output_from(Seed::default(), debug_str, &mut trace)
+    }
 
-        let mut result = RAINBOW.to_string();
-        result.extend(repeat(' ').take(width - 2));
-        result.push(RAINBOW);
-        result.push('\n');
+    /// Number of columns and rows that the decorated `debug_str` takes up.
+    pub fn measure(seed: Seed, debug_str: &str) -> (usize, usize) {
+        PrettierConfig::default().measure(seed, debug_str)
+    }
 
-        for line in debug_str.lines() {
-            result.push(' ');
+    /// Decorates `debug_str` and returns each column of each row with its color, for drawing the
+    /// box with your own text renderer. Every row is padded with spaces to the width from
+    /// [`PrettierPrintDisplayer::measure()`]. A character that is two columns wide is followed
+    /// by a space for its second column. Characters that take up no columns, such as combining
+    /// marks and tabs, belong to the cell before them and are left out. Characters that are not
+    /// colored, which include the stars, get `Color::Reset`.
+    pub fn render_color_grid(seed: Seed, debug_str: &str) -> Vec<Vec<(char, Color)>> {
+        PrettierConfig::default().render_color_grid(seed, debug_str)
+    }
 
-            let leading_space_count = line.bytes().take_while(|&b| b == b' ').count();
+    /// Like [`PrettierPrintDisplayer::output()`], but writes to `w` instead of allocating a
+    /// `String`.
+    pub fn output_fmt<W>(seed: Seed, debug_str: &str, w: &mut W) -> std::fmt::Result
+    where
+        W: std::fmt::Write,
+    {
+        PrettierConfig::default().output_fmt(seed, debug_str, w)
+    }
+}
 
-            // Leading space and content
-            if leading_space_count > 0 && line_rng.next().unwrap() {
-                // Add star to line
-                let star_index = rng.gen_range(0..leading_space_count);
-                result.extend(repeat(' ').take(star_index));
+/// Where the stars go in a box, decided ahead of time by [`PrettierPrinter::plan()`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct DecorationPlan {
+    content_width: usize,
+    /// Column and glyph of the star on each line, if any.
+    lines: Vec<Option<(usize, char)>>,
+}
 
-                result.push(STARS[star_rng.next().unwrap()]);
-                result.extend(repeat(' ').take(leading_space_count - star_index - 1));
+/// Layout of the output. See [`PrettierPrinter::with_style()`].
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Style {
+    /// A box with stars around the `{:#?}` string.
+    Box,
+    /// The `{:?}` string between two border characters on a single line, such as `🌈1🌈`, with
+    /// no stars, padding, or line break. Lines of multi-line text are joined with spaces.
+    InlineCorners,
+}
 
-                result += line.split_at(leading_space_count).1;
-            } else {
-                // No star
-                result.push_str(line);
-            }
+/// Mirrors the output. See [`PrettierPrinter::with_flip()`].
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Flip {
+    None,
+    /// Reverses the characters of each line.
+    Horizontal,
+    /// Reverses the order of the lines.
+    Vertical,
+}
 
-            // Trailing stars
-            if line_rng.next().unwrap() {
-                let star_index = rng.gen_range(0..width - line.len());
-                result.extend(repeat(' ').take(star_index));
-                result.push(STARS[star_rng.next().unwrap()]);
-            }
+/// Whether colors are written.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum ColorChoice {
+    /// Always write colors.
+    Always,
+    /// Never write colors.
+    Never,
+    /// Write colors only if the output is a terminal. When it is not known whether the output is
+    /// a terminal, such as when formatting with `Display`, colors are not written.
+    Auto,
+}
 
-            // Remove extra spaces
-            while result.ends_with(' ') {
-                result.pop();
-            }
+impl ColorChoice {
+    pub(crate) fn enabled<T>(self, output: &T) -> bool
+    where
+        T: IsTty,
+    {
+        match self {
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+            ColorChoice::Auto => output.is_tty(),
+        }
+    }
 
-            result.push('\n');
+    /// Like `enabled()`, for output that might not be a terminal, such as a `String`.
+    fn enabled_unknown(self) -> bool {
+        match self {
+            ColorChoice::Always => true,
+            ColorChoice::Never | ColorChoice::Auto => false,
         }
+    }
+}
 
-        result.push(RAINBOW);
-        result.extend(repeat(' ').take(width - 2));
-        result.push(RAINBOW);
-        result.push('\n');
-        result
+/// Where stars are placed.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum StarLocation {
+    /// In the indentation and after the end of each line.
+    Interior,
+    /// On the top and bottom border lines. The content is left untouched.
+    Border,
+}
+
+/// Error returned by [`PrettierPrinter::try_with_palette()`].
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum PaletteError {
+    /// `stars` and `weights` have different lengths.
+    LengthMismatch { stars: usize, weights: usize },
+    /// No stars were given.
+    Empty,
+    /// Every weight is zero.
+    AllZero,
+    /// The sum of the weights does not fit in a `u16`.
+    TooLarge,
+}
+
+impl Display for PaletteError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PaletteError::LengthMismatch { stars, weights } => write!(
+                f,
+                "each star must have a weight; got {} stars and {} weights",
+                stars, weights
+            ),
+            PaletteError::Empty => write!(f, "palette must have at least one star"),
+            PaletteError::AllZero => write!(f, "at least one weight must be non-zero"),
+            PaletteError::TooLarge => write!(f, "sum of weights must fit in a u16"),
+        }
     }
 }
 
-impl<T> Display for PrettierPrintDisplayer<'_, T>
-where
-    T: Debug,
-{
+impl std::error::Error for PaletteError {}
+
+/// Error returned by [`PrettierPrinter::try_with_emoji_width()`].
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct EmojiWidthError {
+    /// The width that was given.
+    pub emoji_width: usize,
+}
+
+impl Display for EmojiWidthError {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(
-            f,
-            "{}",
-            PrettierPrintDisplayer::<T>::output(self.seed, &format!("{:#?}", self.inner))
-        )
+        write!(f, "emoji width must be 1 or 2; got {}", self.emoji_width)
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use std::collections::HashMap;
+impl std::error::Error for EmojiWidthError {}
 
-    #[test]
-    fn prettier_printer() {
-        let seed = {
-            let mut seed = Seed::default();
-            seed[0] = 180; // Good seed for example
-            seed
-        };
-        {
-            let result = PrettierPrinter::new_with_seed(seed).print(&0).to_string();
-            let expected = r"🌈 🌈
- 0
-🌈 🌈
-";
-            assert_eq!(result, expected);
-        }
-        {
-            #[derive(Debug, Clone)]
-            struct Type {
-                a: String,
-                b: Vec<i32>,
-                c: HashMap<&'static str, &'static str>,
+/// Error returned by [`PrettierPrinter::try_with_config()`]. Each variant is a field of
+/// [`PrettierConfig`] with a value that the `with_*` method of the field would not accept.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum ConfigError {
+    Palette(PaletteError),
+    EmojiWidth(EmojiWidthError),
+    /// `bytes_per_row` is zero.
+    BytesPerRow,
+    DensityPerColumn(f64),
+    /// `column_split` is zero.
+    ColumnSplit,
+    StarDensity(f64),
+    Intensity(f32),
+    SparkleRatio(u32, u32),
+}
+
+impl Display for ConfigError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConfigError::Palette(e) => write!(f, "{}", e),
+            ConfigError::EmojiWidth(e) => write!(f, "{}", e),
+            ConfigError::BytesPerRow => write!(f, "bytes_per_row must be non-zero"),
+            ConfigError::DensityPerColumn(density) => {
+                write!(f, "density must be between 0 and 1; got {}", density)
             }
+            ConfigError::ColumnSplit => write!(f, "column_count must be non-zero"),
+            ConfigError::StarDensity(density) => write!(
+                f,
+                "stars_per_100_cells must not be negative; got {}",
+                density
+            ),
+            ConfigError::Intensity(intensity) => write!(f, "invalid intensity {}", intensity),
+            ConfigError::SparkleRatio(numerator, denominator) => {
+                write!(f, "invalid ratio {}/{}", numerator, denominator)
+            }
+        }
+    }
+}
 
-            let input = Type {
-                a: "a".to_string(),
-                b: vec![0, 1],
-                c: {
-                    let mut map = HashMap::new();
-                    map.insert("So", "pretty");
-                    map
-                },
-            };
+impl std::error::Error for ConfigError {}
 
-            let displayer = PrettierPrinter::new_with_seed(seed).print(&input);
+/// Decoration settings shared by `PrettierPrinter` and the displayers it creates. Set them all at
+/// once with [`PrettierPrinter::with_config()`]. Start from [`PrettierConfig::default()`] to keep
+/// the defaults of the fields that are not set.
+#[derive(Debug, Clone)]
+pub struct PrettierConfig {
+    /// See [`PrettierPrinter::with_emoji_width()`].
+    pub emoji_width: usize,
+    /// Whether a star in the indentation takes the place of as many spaces as it is wide. Set by
+    /// [`PrettierPrinter::with_emoji_width()`].
+    pub sized_stars: bool,
+    /// See [`PrettierPrinter::with_star_location()`].
+    pub star_location: StarLocation,
+    /// See [`PrettierPrinter::with_line_seeds()`].
+    pub line_seeds: Vec<Seed>,
+    /// See [`PrettierPrinter::with_rainbow_text()`].
+    pub rainbow_text: bool,
+    /// See [`PrettierPrinter::with_color()`].
+    pub color: ColorChoice,
+    /// See [`PrettierPrinter::with_border()`].
+    pub border: char,
+    /// Stars to choose from. See [`PrettierPrinter::with_palette()`].
+    pub stars: Vec<char>,
+    /// Weight of each star. See [`PrettierPrinter::with_palette()`].
+    pub weights: Vec<u16>,
+    /// See [`PrettierPrinter::with_bytes_per_row()`].
+    pub bytes_per_row: usize,
+    /// See [`PrettierPrinter::with_density_per_column()`].
+    pub density_per_column: Option<f64>,
+    /// See [`PrettierPrinter::with_column_split()`].
+    pub column_split: usize,
+    /// See [`PrettierPrinter::with_star_density()`].
+    pub star_density: Option<f64>,
+    /// See [`PrettierPrinter::with_max_total_stars()`].
+    pub max_total_stars: Option<usize>,
+    /// See [`PrettierPrinter::with_fill()`].
+    pub fill: char,
+    /// Width of the content; lines are truncated to fit. See
+    /// [`PrettierPrinter::print_with_width_hint()`].
+    pub width_hint: Option<usize>,
+    /// See [`PrettierPrinter::with_indent()`].
+    pub indent: usize,
+    /// See [`PrettierPrinter::with_collection_summary()`].
+    pub collection_summary: Option<usize>,
+    /// See [`PrettierPrinter::with_frame_rainbow()`].
+    pub frame_rainbow: bool,
+    /// See [`PrettierPrinter::with_shadow()`].
+    pub shadow: bool,
+    /// Numerator and denominator. See [`PrettierPrinter::with_sparkle_ratio()`].
+    pub sparkle_ratio: (u32, u32),
+    /// See [`PrettierPrinter::with_flip()`].
+    pub flip: Flip,
+    /// See [`PrettierPrinter::with_decorrelated_lines()`].
+    pub decorrelated_lines: bool,
+    /// See [`PrettierPrinter::with_intensity()`].
+    pub intensity: f32,
+    /// See [`PrettierPrinter::with_line_transform()`].
+    pub line_transform: Option<LineTransform>,
+    /// See [`PrettierPrinter::with_redact()`].
+    pub redact: Vec<String>,
+    /// Left and right margins. See [`PrettierPrinter::with_interior_margin()`].
+    pub interior_margin: (usize, usize),
+    /// See [`PrettierPrinter::with_detect_existing_border()`].
+    pub detect_existing_border: bool,
+    /// See [`PrettierPrinter::with_width_fn()`].
+    pub width_fn: Option<WidthFn>,
+    /// See [`PrettierPrinter::with_breadcrumbs()`].
+    pub breadcrumbs: bool,
+    /// See [`PrettierPrinter::with_style()`].
+    pub style: Style,
+}
 
-            let result = displayer.to_string();
-            let expected = r#"🌈                         🌈
- Type {
- ⭐   a: "a",
-   ⭐ b: [    ⭐
-      🦀  0,
-        ⭐1,    ⭐
-  🌟  ],
-   ⭐ c: {
- ⭐       "So": "pretty",   ⭐
-   ⭐ },       ⭐
- }             ☀
-🌈                         🌈
-"#;
-            assert_eq!(result, expected);
-            // Check if cloned Displayer outputs the same string
-            assert_eq!(displayer.clone().to_string(), expected);
+/// Function from [`PrettierPrinter::with_line_transform()`].
+#[derive(Clone)]
+pub struct LineTransform(Arc<dyn Fn(&str) -> String + Send + Sync>);
 
-            println!("\n{:#?}\n\n", &input);
-            println!("{}\n", result);
+impl LineTransform {
+    /// Wraps `transform`, which is called with each line and returns the line to show instead.
+    pub fn new<F>(transform: F) -> Self
+    where
+        F: Fn(&str) -> String + Send + Sync + 'static,
+    {
+        Self(Arc::new(transform))
+    }
+}
+
+impl Debug for LineTransform {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str("LineTransform")
+    }
+}
+
+/// Function from [`PrettierPrinter::with_width_fn()`].
+#[derive(Clone)]
+pub struct WidthFn(Arc<dyn Fn(char) -> usize + Send + Sync>);
+
+impl WidthFn {
+    /// Wraps `width_fn`, which returns the number of columns a character takes up.
+    pub fn new<F>(width_fn: F) -> Self
+    where
+        F: Fn(char) -> usize + Send + Sync + 'static,
+    {
+        Self(Arc::new(width_fn))
+    }
+
+    /// Sum of the widths of the characters of `s`.
+    pub(crate) fn str_width(&self, s: &str) -> usize {
+        s.chars().map(|c| (self.0)(c)).sum()
+    }
+}
+
+impl Debug for WidthFn {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str("WidthFn")
+    }
+}
+
+impl Default for PrettierConfig {
+    fn default() -> Self {
+        Self {
+            emoji_width: 2,
+            sized_stars: false,
+            star_location: StarLocation::Interior,
+            line_seeds: Vec::new(),
+            rainbow_text: false,
+            color: ColorChoice::Always,
+            border: DEFAULT_BORDER,
+            stars: DEFAULT_STARS.to_vec(),
+            weights: DEFAULT_WEIGHTS.to_vec(),
+            bytes_per_row: 16,
+            density_per_column: None,
+            column_split: 1,
+            star_density: None,
+            max_total_stars: None,
+            fill: ' ',
+            width_hint: None,
+            indent: 0,
+            collection_summary: None,
+            frame_rainbow: false,
+            shadow: false,
+            sparkle_ratio: (3, 5),
+            flip: Flip::None,
+            decorrelated_lines: false,
+            intensity: 1.0,
+            line_transform: None,
+            redact: Vec::new(),
+            interior_margin: (0, 0),
+            detect_existing_border: false,
+            width_fn: None,
+            breadcrumbs: false,
+            style: Style::Box,
         }
     }
 }
+
+/// Character used at the corners of the box by default.
+pub const DEFAULT_BORDER: char = '🌈';
+/// Stars used by default.
+pub const DEFAULT_STARS: [char; 4] = ['⭐', '🌟', '☀', '🦀'];
+/// How often each of `DEFAULT_STARS` is chosen relative to the others.
+pub const DEFAULT_WEIGHTS: [u16; 4] = [1500, 300, 100, 1];
+
+/// Number of spaces between the panels of [`PrettierPrinter::with_column_split()`].
+const COLUMN_SPLIT_GAP: usize = 4;
+
+/// Marks added lines in [`PrettierPrinter::print_diff()`].
+pub const DIFF_ADDED: &str = "➕ ";
+/// Marks removed lines in [`PrettierPrinter::print_diff()`].
+pub const DIFF_REMOVED: &str = "➖ ";
+
+/// Number of spaces between the boxes of [`PrettierPrinter::contact_sheet()`].
+const CONTACT_SHEET_GAP: usize = 2;
+
+/// Horizontal edge of [`PrettierPrinter::with_frame_rainbow()`].
+const FRAME_HORIZONTAL: char = '─';
+/// Vertical edge of [`PrettierPrinter::with_frame_rainbow()`].
+const FRAME_VERTICAL: char = '│';
+
+/// Character of [`PrettierPrinter::with_shadow()`].
+const SHADOW: char = '░';
+
+/// Starts the footer of [`PrettierPrinter::print_timed()`].
+const TIMED_FOOTER: &str = "formatted in";
+
+/// Approximate distance between stars on a border line.
+const BORDER_STAR_SPACING: usize = 8;
+
+impl PrettierConfig {
+    /// Checks each field like its `with_*` method does.
+    pub(crate) fn validate(&self) -> Result<(), ConfigError> {
+        check_palette(&self.stars, &self.weights).map_err(ConfigError::Palette)?;
+        if !matches!(self.emoji_width, 1 | 2) {
+            return Err(ConfigError::EmojiWidth(EmojiWidthError {
+                emoji_width: self.emoji_width,
+            }));
+        }
+        if self.bytes_per_row == 0 {
+            return Err(ConfigError::BytesPerRow);
+        }
+        match self.density_per_column {
+            Some(density) if !(0.0..=1.0).contains(&density) => {
+                return Err(ConfigError::DensityPerColumn(density));
+            }
+            _ => {}
+        }
+        if self.column_split == 0 {
+            return Err(ConfigError::ColumnSplit);
+        }
+        match self.star_density {
+            Some(density) if density < 0.0 || density.is_nan() => {
+                return Err(ConfigError::StarDensity(density));
+            }
+            _ => {}
+        }
+        if !(0.0..=1.0).contains(&self.intensity) {
+            return Err(ConfigError::Intensity(self.intensity));
+        }
+        let (numerator, denominator) = self.sparkle_ratio;
+        if denominator == 0 || numerator > denominator {
+            return Err(ConfigError::SparkleRatio(numerator, denominator));
+        }
+        Ok(())
+    }
+
+    /// Like `output()`, but makes random decisions with `trace`.
+    #[cfg(feature = "rng-trace")]
+    fn output_from(&self, seed: Seed, debug_str: &str, trace: &mut Trace) -> String {
+        let mut result = String::new();
+        let color = self.color.enabled_unknown();
+        self.render_from(seed, debug_str, &mut result, color, trace)
+            .unwrap(); // Writing to String never fails
+        result
+    }
+
+    pub(crate) fn output(&self, seed: Seed, debug_str: &str) -> String {
+        let mut result = String::new();
+        self.output_fmt(seed, debug_str, &mut result).unwrap(); // Writing to String never fails
+        result
+    }
+
+    /// Writes the output to `w`. With `ColorChoice::Auto`, colors are not written.
+    pub(crate) fn output_fmt<W>(&self, seed: Seed, debug_str: &str, w: &mut W) -> std::fmt::Result
+    where
+        W: std::fmt::Write,
+    {
+        let color = self.color.enabled_unknown();
+        self.render(seed, debug_str, w, color)
+    }
+
+    /// Writes the output to `w`. With `ColorChoice::Auto`, colors are written if `w` is a
+    /// terminal.
+    pub(crate) fn output_to<W>(&self, seed: Seed, debug_str: &str, w: &mut W) -> io::Result<()>
+    where
+        W: io::Write + IsTty,
+    {
+        let mut result = String::new();
+        self.render(seed, debug_str, &mut result, self.color.enabled(w))
+            .unwrap(); // Writing to String never fails
+        w.write_all(result.as_bytes())
+    }
+
+    pub(crate) fn measure(&self, seed: Seed, debug_str: &str) -> (usize, usize) {
+        let mut result = String::new();
+        self.render(seed, debug_str, &mut result, false).unwrap(); // Writing to String never fails
+        let width = result
+            .lines()
+            .map(|line| self.display_width(line))
+            .max()
+            .unwrap_or(0);
+        (width, result.lines().count())
+    }
+
+    pub(crate) fn render_color_grid(&self, seed: Seed, debug_str: &str) -> Vec<Vec<(char, Color)>> {
+        let mut result = String::new();
+        let mut trace = Trace {
+            stars: Some(Vec::new()),
+            ..Trace::default()
+        };
+        self.render_from(seed, debug_str, &mut result, true, &mut trace)
+            .unwrap(); // Writing to String never fails
+
+        let mut grid: Vec<Vec<(char, Color)>> = result
+            .lines()
+            .map(|line| {
+                let mut row = Vec::new();
+                for (c, color) in color::parse_colored(line) {
+                    let width = self.char_width(c);
+                    // Attached to the previous cell, so that the columns match the stars
+                    if width == 0 {
+                        continue;
+                    }
+                    row.push((c, color));
+                    row.extend(repeat((' ', Color::Reset)).take(width - 1));
+                }
+                row
+            })
+            .collect();
+        let width = grid.iter().map(Vec::len).max().unwrap_or(0);
+        for row in &mut grid {
+            row.resize(width, (' ', Color::Reset));
+        }
+        // Stars are written without a color, so they get the default color of the terminal
+        for (row, column, star) in trace.stars.into_iter().flatten() {
+            debug_assert_eq!(grid[row][column].0, star);
+            grid[row][column] = (star, Color::Reset);
+        }
+        grid
+    }
+
+    pub(crate) fn plan(&self, seed: Seed, content_width: usize, height: usize) -> DecorationPlan {
+        let width = box_width(content_width);
+        let mut stars = StarRng::new(seed, self);
+        let lines = (0..height)
+            .map(|_| {
+                if stars.sparkle() {
+                    // After the content, so that the star fits if lines are not too long
+                    Some((stars.position(1 + content_width..width), stars.star()))
+                } else {
+                    None
+                }
+            })
+            .collect();
+        DecorationPlan {
+            content_width,
+            lines,
+        }
+    }
+
+    pub(crate) fn apply_plan(&self, plan: &DecorationPlan, debug_str: &str) -> String {
+        let mut result = String::new();
+        let color = self.color.enabled_unknown();
+        let mut trace = Trace {
+            plan: Some(plan.lines.clone()),
+            ..Trace::default()
+        };
+        let width = box_width(plan.content_width);
+        let lines = debug_str.lines().map(Ok);
+        self.render_lines(Seed::default(), lines, width, color, &mut trace, |s| {
+            result.push_str(s);
+            Ok::<(), Infallible>(())
+        })
+        .unwrap(); // Writing to String never fails
+        result
+    }
+
+    pub(crate) fn queue_decorated<W>(
+        &self,
+        w: &mut W,
+        seed: Seed,
+        debug_str: &str,
+        origin: (u16, u16),
+    ) -> crossterm::Result<()>
+    where
+        W: io::Write,
+    {
+        for (row, line) in self.output(seed, debug_str).lines().enumerate() {
+            // MoveTo adds one to the row, so u16::MAX cannot be moved to
+            let y = match u16::try_from(row)
+                .ok()
+                .and_then(|row| origin.1.checked_add(row))
+            {
+                Some(y) if y < u16::MAX => y,
+                _ => break,
+            };
+            queue!(w, MoveTo(origin.0, y), Print(line))?;
+        }
+        Ok(())
+    }
+
+    pub(crate) fn render<W>(
+        &self,
+        seed: Seed,
+        debug_str: &str,
+        w: &mut W,
+        color: bool,
+    ) -> std::fmt::Result
+    where
+        W: std::fmt::Write,
+    {
+        self.render_from(seed, debug_str, w, color, &mut Trace::default())
+    }
+
+    /// Like `render()`, but makes random decisions with `trace` and records the stars in it.
+    fn render_from<W>(
+        &self,
+        seed: Seed,
+        debug_str: &str,
+        w: &mut W,
+        color: bool,
+        trace: &mut Trace,
+    ) -> std::fmt::Result
+    where
+        W: std::fmt::Write,
+    {
+        if self.style == Style::InlineCorners {
+            w.write_char(self.border)?;
+            for (i, line) in debug_str.lines().enumerate() {
+                if i > 0 {
+                    w.write_char(' ')?;
+                }
+                w.write_str(line)?;
+            }
+            return w.write_char(self.border);
+        }
+
+        if self.detect_existing_border && is_framed(debug_str) {
+            return w.write_str(&self.decorate_frame(seed, debug_str, trace));
+        }
+
+        match self.flip {
+            Flip::None => self.render_unflipped(seed, debug_str, w, color, trace),
+            Flip::Horizontal => {
+                let mut result = String::new();
+                self.render_unflipped(seed, debug_str, &mut result, color, trace)?;
+                if let Some(stars) = &mut trace.stars {
+                    let width = result
+                        .lines()
+                        .map(|line| self.display_width(&color::strip_ansi(line)))
+                        .max();
+                    for (_, column, star) in stars {
+                        *column = width.unwrap_or(0) - *column - self.char_width(*star);
+                    }
+                }
+                w.write_str(&flip_horizontal(&result, |s| self.display_width(s)))
+            }
+            Flip::Vertical => {
+                let mut result = String::new();
+                self.render_unflipped(seed, debug_str, &mut result, color, trace)?;
+                let height = result.lines().count();
+                for (row, _, _) in trace.stars.iter_mut().flatten() {
+                    *row = height - 1 - *row;
+                }
+                for line in result.lines().rev() {
+                    w.write_str(line)?;
+                    w.write_char('\n')?;
+                }
+                Ok(())
+            }
+        }
+    }
+
+    fn render_unflipped<W>(
+        &self,
+        seed: Seed,
+        debug_str: &str,
+        w: &mut W,
+        color: bool,
+        trace: &mut Trace,
+    ) -> std::fmt::Result
+    where
+        W: std::fmt::Write,
+    {
+        let summary;
+        let debug_str = if let Some(max_items) = self.collection_summary {
+            summary = outline::summarize_collections(debug_str, max_items);
+            &summary
+        } else {
+            debug_str
+        };
+
+        let split;
+        let debug_str = if self.column_split > 1 {
+            split = layout::split_columns(debug_str, self.column_split, COLUMN_SPLIT_GAP, |s| {
+                self.display_width(s)
+            });
+            &split
+        } else {
+            debug_str
+        };
+
+        let redacted;
+        let debug_str = if self.redact.is_empty() {
+            debug_str
+        } else {
+            redacted = redact(debug_str, &self.redact);
+            &redacted
+        };
+
+        // Written before each line, apart from the content
+        trace.prefixes = if self.breadcrumbs {
+            outline::breadcrumbs(debug_str, |s| self.display_width(s))
+        } else {
+            Vec::new()
+        };
+        let prefix_width = trace
+            .prefixes
+            .first()
+            .map_or(0, |prefix| self.display_width(prefix));
+
+        let transformed: String;
+        let debug_str = if let Some(LineTransform(transform)) = &self.line_transform {
+            transformed = debug_str
+                .lines()
+                .map(|line| transform(line) + "\n")
+                .collect();
+            &transformed
+        } else {
+            debug_str
+        };
+
+        if let Some(content_width) = self.width_hint {
+            let width = box_width(prefix_width + content_width);
+            let budgeted;
+            let this = if let Some(star_density) = self.star_density {
+                let line_widths = debug_str
+                    .lines()
+                    .map(|line| prefix_width + self.display_width(line).min(content_width));
+                budgeted = self.with_star_budget(star_density, width, line_widths);
+                &budgeted
+            } else {
+                self
+            };
+            let lines = debug_str.lines().map(|line| {
+                Ok(safe_truncate(line, content_width, |s| {
+                    this.display_width(s)
+                }))
+            });
+            return this.render_lines(seed, lines, width, color, trace, |s| w.write_str(s));
+        }
+
+        let width = self
+            .widest_line(debug_str)
+            .map_or(0, |widest| box_width(prefix_width + widest));
+        let budgeted;
+        let this = if let Some(star_density) = self.star_density {
+            let line_widths = debug_str
+                .lines()
+                .map(|line| prefix_width + self.display_width(line));
+            budgeted = self.with_star_budget(star_density, width, line_widths);
+            &budgeted
+        } else {
+            self
+        };
+        this.render_lines(seed, debug_str.lines().map(Ok), width, color, trace, |s| {
+            w.write_str(s)
+        })
+    }
+
+    /// Copy of the config that places about `star_density` stars per 100 cells of a box `width`
+    /// wide with lines `line_widths` wide. The budget is spread over the space after the content
+    /// with a per-column chance, and leading stars are turned off.
+    fn with_star_budget<I>(&self, star_density: f64, width: usize, line_widths: I) -> Self
+    where
+        I: Iterator<Item = usize>,
+    {
+        let mut line_count = 0;
+        let mut available = 0;
+        for line_width in line_widths {
+            line_count += 1;
+            available += width.saturating_sub(line_width);
+        }
+        let budget = ((width * line_count) as f64 * star_density / 100.0).round() as usize;
+
+        // Each star takes glyph_width() columns, so fewer columns are left for the others
+        let star_columns = (budget * self.glyph_width().saturating_sub(1)) as f64;
+        let density = if budget == 0 {
+            0.0
+        } else if available as f64 > star_columns + budget as f64 {
+            budget as f64 / (available as f64 - star_columns)
+        } else {
+            1.0
+        };
+
+        let mut config = self.clone();
+        config.density_per_column = Some(density.min(1.0));
+        config.max_total_stars = Some(self.max_total_stars.map_or(budget, |max| max.min(budget)));
+        config.sparkle_ratio = (0, 1);
+        config
+    }
+
+    /// Debug string of `inner` for [`PrettierPrintDisplayer`]: `{:?}` for
+    /// [`Style::InlineCorners`] and `{:#?}` otherwise.
+    pub(crate) fn debug_string<T>(&self, inner: &T) -> String
+    where
+        T: Debug,
+    {
+        match self.style {
+            Style::Box => format!("{:#?}", inner),
+            Style::InlineCorners => format!("{:?}", inner),
+        }
+    }
+
+    /// Number of columns `s` takes up with [`PrettierPrinter::with_width_fn()`], or with the
+    /// Unicode width and [`PrettierPrinter::with_emoji_width()`] if it is not set.
+    pub fn display_width(&self, s: &str) -> usize {
+        match &self.width_fn {
+            Some(width_fn) => width_fn.str_width(s),
+            None => display_width(s, self.emoji_width),
+        }
+    }
+
+    /// Number of columns `c` takes up. See [`PrettierPrinter::with_width_fn()`].
+    fn char_width(&self, c: char) -> usize {
+        match &self.width_fn {
+            Some(WidthFn(width_fn)) => width_fn(c),
+            None => width::char_width(c, self.emoji_width),
+        }
+    }
+
+    /// Number of columns the border and each star take up.
+    fn glyph_width(&self) -> usize {
+        match &self.width_fn {
+            Some(WidthFn(width_fn)) => width_fn(self.border),
+            None => self.emoji_width,
+        }
+    }
+
+    /// Width of the widest line of `s`, or `None` if `s` is empty. Printable ASCII is measured by
+    /// its length, without looking up the width of each character.
+    fn widest_line(&self, s: &str) -> Option<usize> {
+        if self.width_fn.is_none() && is_printable_ascii(s) {
+            widest_line_ascii(s)
+        } else {
+            s.lines().map(|s| self.display_width(s)).max()
+        }
+    }
+
+    /// Decorates `lines` in a box that is `width` columns wide, passing each part of the output to
+    /// `write` as soon as it is ready.
+    fn render_lines<I, S, E, F>(
+        &self,
+        seed: Seed,
+        lines: I,
+        width: usize,
+        color: bool,
+        trace: &mut Trace,
+        mut write: F,
+    ) -> Result<(), E>
+    where
+        I: IntoIterator<Item = Result<S, E>>,
+        S: AsRef<str>,
+        F: FnMut(&str) -> Result<(), E>,
+    {
+        let color = color && self.intensity > 0.0;
+        let mut stars = StarRng::new(seed, self);
+        stars.trace = take(trace);
+        let indent = " ".repeat(self.indent);
+        let (left_margin, right_margin) = self.interior_margin;
+        let width = width + left_margin + right_margin;
+        let mut margined = String::new();
+
+        write(&indent)?;
+        write(&self.border(width, &mut stars, color))?;
+        stars.trace.row += 1;
+        let mut buffer = String::new();
+        for (i, line) in lines.into_iter().enumerate() {
+            let line = line?;
+            let line = if left_margin > 0 {
+                margined.clear();
+                margined.extend(repeat(' ').take(left_margin));
+                margined.push_str(line.as_ref());
+                &margined
+            } else {
+                line.as_ref()
+            };
+            buffer.clear();
+            let used = if self.line_seeds.is_empty() && !self.decorrelated_lines {
+                self.push_line(&mut buffer, line, width, &mut stars, color)
+            } else {
+                let seed = if self.line_seeds.is_empty() {
+                    decorrelated_seed(seed, i)
+                } else {
+                    self.line_seeds[i % self.line_seeds.len()]
+                };
+                let mut line_stars = StarRng::new(seed, self);
+                line_stars.remaining = stars.remaining;
+                line_stars.trace = take(&mut stars.trace);
+                let used = self.push_line(&mut buffer, line, width, &mut line_stars, color);
+                stars.remaining = line_stars.remaining;
+                stars.trace = take(&mut line_stars.trace);
+                used
+            };
+            if self.shadow {
+                self.push_shadow(&mut buffer, width, used);
+            }
+            // Written separately so that removing trailing spaces does not remove the indent
+            write(&indent)?;
+            write(&buffer)?;
+            stars.trace.row += 1;
+        }
+        write(&indent)?;
+        let mut bottom = self.border(width, &mut stars, color);
+        *trace = take(&mut stars.trace); // The bottom border is the last decision
+        if self.shadow {
+            self.push_shadow(&mut bottom, width, width + self.glyph_width());
+            write(&bottom)?;
+            let mut shadow = indent;
+            shadow.push(' ');
+            shadow.extend(repeat(SHADOW).take(width + self.glyph_width()));
+            shadow.push('\n');
+            write(&shadow)
+        } else {
+            write(&bottom)
+        }
+    }
+
+    /// Adds stars to the top and bottom edges of `framed`, which is a string that
+    /// [`is_framed()`].
+    fn decorate_frame(&self, seed: Seed, framed: &str, trace: &mut Trace) -> String {
+        let mut stars = StarRng::new(seed, self);
+        stars.trace = take(trace);
+        let lines: Vec<&str> = framed.trim_end_matches('\n').lines().collect();
+        let mut result = String::new();
+        for (i, line) in lines.iter().enumerate() {
+            if i == 0 || i == lines.len() - 1 {
+                result.push_str(&self.decorate_edge(line.trim_end(), &mut stars));
+            } else {
+                result.push_str(line);
+            }
+            result.push('\n');
+            stars.trace.row += 1;
+        }
+        *trace = take(&mut stars.trace);
+        result
+    }
+
+    /// Replaces parts of `edge`, the top or bottom line of a frame, with stars. The corners are
+    /// kept.
+    fn decorate_edge(&self, edge: &str, stars: &mut StarRng) -> String {
+        let chars: Vec<char> = edge.chars().collect();
+        let inner = &chars[1..chars.len() - 1];
+        let mut result = String::new();
+        result.push(chars[0]);
+        for (i, segment) in inner.chunks(BORDER_STAR_SPACING).enumerate() {
+            let star = if segment.len() >= self.glyph_width() && stars.sparkle() && stars.take() {
+                // A star can be wider than the border, such as with a custom palette
+                Some(stars.star()).filter(|&star| segment.len() >= self.char_width(star).max(1))
+            } else {
+                None
+            };
+            if let Some(star) = star {
+                // Replace as many edge characters, which are one column each, as the star covers
+                let star_width = self.char_width(star).max(1);
+                let star_index = stars.position(0..segment.len() - star_width + 1);
+                result.extend(&segment[..star_index]);
+                result.push(star);
+                let column = self.char_width(chars[0]) + i * BORDER_STAR_SPACING + star_index;
+                stars.place(column, star);
+                result.extend(&segment[star_index + star_width..]);
+            } else {
+                result.extend(segment);
+            }
+        }
+        result.push(chars[chars.len() - 1]);
+        result
+    }
+
+    /// Adds a shadow to the right of `line`, which is a line of a box that is `width` columns
+    /// wide, takes up `used` columns and ends with a line break. Stars are kept inside the box, so
+    /// the shadow is only pushed out by a line that is longer than the box, such as from
+    /// [`PrettierPrinter::decorate_reader()`] with a `content_width` that is too small.
+    fn push_shadow(&self, line: &mut String, width: usize, used: usize) {
+        line.pop();
+        line.extend(repeat(' ').take((width + self.glyph_width()).saturating_sub(used)));
+        line.push(SHADOW);
+        line.push('\n');
+    }
+
+    fn border(&self, width: usize, stars: &mut StarRng, color: bool) -> String {
+        let padding = width.saturating_sub(self.glyph_width());
+        let fill = if self.frame_rainbow {
+            FRAME_HORIZONTAL
+        } else {
+            self.fill
+        };
+
+        let mut result = self.border.to_string();
+        let planned = stars.trace.plan.is_some();
+        if self.star_location == StarLocation::Border && padding >= self.glyph_width() && !planned {
+            // Place one star in each segment of the border
+            let segment_count = (padding / BORDER_STAR_SPACING).max(1);
+            let segment_length = padding / segment_count;
+            for segment in 0..segment_count {
+                let star_index = stars.position(0..segment_length - self.glyph_width() + 1);
+                result.extend(repeat(fill).take(star_index));
+                let star = stars.star();
+                if stars.take() {
+                    result.push(star);
+                    let column = self.glyph_width() + segment * segment_length + star_index;
+                    stars.place(self.indent + column, star);
+                } else {
+                    result.extend(repeat(fill).take(self.glyph_width()));
+                }
+                result.extend(repeat(fill).take(segment_length - star_index - self.glyph_width()));
+            }
+            result.extend(repeat(fill).take(padding - segment_length * segment_count));
+        } else {
+            result.extend(repeat(fill).take(padding));
+        }
+        result.push(self.border);
+        result.push('\n');
+
+        if self.frame_rainbow && color {
+            let mut tinted = String::new();
+            let mut column = 0;
+            for c in result.chars() {
+                if c == FRAME_HORIZONTAL {
+                    self.push_edge(&mut tinted, c, column, color);
+                } else {
+                    tinted.push(c);
+                }
+                column += self.char_width(c);
+            }
+            result = tinted;
+        }
+        result
+    }
+
+    /// Appends `line` and its stars to `result`, and returns the number of columns that were
+    /// appended, not counting the line break.
+    fn push_line(
+        &self,
+        result: &mut String,
+        line: &str,
+        width: usize,
+        stars: &mut StarRng,
+        color: bool,
+    ) -> usize {
+        if self.frame_rainbow {
+            self.push_edge(result, FRAME_VERTICAL, 0, color);
+        } else {
+            result.push(' ');
+        }
+        let mut used = 1;
+        let prefix = stars.prefix();
+        if !prefix.is_empty() {
+            self.push_prefix(result, prefix, color);
+            used += self.display_width(prefix);
+        }
+        let start = used;
+
+        let planned = stars.planned();
+        if self.star_location == StarLocation::Interior || planned.is_some() {
+            let leading_space_count = line.bytes().take_while(|&b| b == b' ').count();
+            let line_width = self.display_width(line);
+            // Columns that a star in the indent pushes the content to the right
+            let mut shift = 0;
+
+            // Leading space and content
+            let fits = if self.sized_stars {
+                leading_space_count >= self.glyph_width().max(1)
+            } else {
+                leading_space_count > 0
+            };
+            let star = if planned.is_none() && fits && stars.sparkle() && stars.take() {
+                // A star that is wider than the indent would push the content to the right
+                Some(stars.star()).filter(|&star| {
+                    !self.sized_stars || self.char_width(star) <= leading_space_count
+                })
+            } else {
+                None
+            };
+            if let Some(star) = star {
+                // Add star to line in place of as many spaces as it is wide, or of one space
+                let star_width = if self.sized_stars {
+                    self.char_width(star).max(1)
+                } else {
+                    1
+                };
+                let star_index = stars.position(0..leading_space_count - star_width + 1);
+                result.extend(repeat(' ').take(star_index));
+
+                result.push(star);
+                stars.place(self.indent + start + star_index, star);
+                result.extend(repeat(' ').take(leading_space_count - star_index - star_width));
+
+                let content = line.split_at(leading_space_count).1;
+                self.push_content(result, content, start + leading_space_count, color);
+                shift = self.char_width(star).saturating_sub(star_width);
+                used += (line_width + self.char_width(star)).saturating_sub(star_width);
+            } else {
+                // No star
+                self.push_content(result, line, start, color);
+                used += line_width;
+            }
+
+            // Trailing stars, which end before the right edge of the frame or after the box
+            let end = if self.frame_rainbow {
+                self.right_edge(width)
+            } else {
+                width + self.glyph_width()
+            };
+            let end = end.saturating_sub(self.interior_margin.1);
+            // Without sized stars, the shift may push a trailing star past the box, unless it
+            // has to line up with the frame or the shadow
+            let unshifted = if self.frame_rainbow || self.shadow {
+                used
+            } else {
+                used - shift
+            };
+            let available = (end + 1).saturating_sub(self.glyph_width() + unshifted);
+            let mut trailing = String::new();
+            // Columns of trailing up to the end of the last star
+            let mut trailing_width = 0;
+            if let Some(planned) = planned {
+                if let Some((column, star)) = planned {
+                    // The planned column does not count the left margin
+                    let column = column + self.interior_margin.0;
+                    if column >= used && column - used < available {
+                        let star_index = column - used;
+                        trailing.extend(repeat(self.fill).take(star_index));
+                        trailing.push(star);
+                        stars.place(self.indent + column, star);
+                        trailing_width = star_index + self.char_width(star);
+                    }
+                }
+            } else if let Some(density) = self.density_per_column {
+                let mut column = 0;
+                while column < available {
+                    let star = if stars.chance(density) && stars.take() {
+                        // Stars end before the end of the box, even if wider than the border
+                        Some(stars.star()).filter(|&star| {
+                            column + self.char_width(star) < available + self.glyph_width()
+                        })
+                    } else {
+                        None
+                    };
+                    if let Some(star) = star {
+                        trailing.push(star);
+                        stars.place(self.indent + used + column, star);
+                        trailing_width = column + self.char_width(star);
+                        column += self.char_width(star).max(1);
+                    } else {
+                        trailing.push(self.fill);
+                        column += 1;
+                    }
+                }
+            } else if available > 0 && stars.sparkle() && stars.take() {
+                let star = stars.star();
+                // Stars end before the end of the box, even if wider than the border
+                let end = (available + self.glyph_width()).saturating_sub(self.char_width(star));
+                let end = end.min(available);
+                if end > 0 {
+                    let star_index = stars.position(0..end);
+                    trailing.extend(repeat(self.fill).take(star_index));
+                    trailing.push(star);
+                    stars.place(self.indent + used + star_index, star);
+                    trailing_width = star_index + self.char_width(star);
+                }
+            }
+            // Only remove fill that was added after the content
+            result.push_str(trailing.trim_end_matches([' ', self.fill]));
+            used += trailing_width;
+        } else {
+            self.push_content(result, line, start, color);
+            used += self.display_width(line);
+        }
+
+        // Remove extra spaces
+        while result.ends_with(' ') {
+            result.pop();
+            used = used.saturating_sub(1);
+        }
+
+        if self.frame_rainbow {
+            let right_edge = self.right_edge(width);
+            result.extend(repeat(' ').take(right_edge.saturating_sub(used)));
+            self.push_edge(result, FRAME_VERTICAL, right_edge, color);
+            used = used.max(right_edge) + 1;
+        }
+
+        result.push('\n');
+        used
+    }
+
+    /// Column of the right edge of the frame, which lines up with the last column of the corner.
+    fn right_edge(&self, width: usize) -> usize {
+        (width + self.glyph_width()).saturating_sub(1)
+    }
+
+    /// Color of `column` in a rainbow that is dimmed by [`PrettierPrinter::with_intensity()`].
+    fn rainbow(&self, column: usize) -> Color {
+        color::scale(color::rainbow(column), self.intensity)
+    }
+
+    /// Appends a character of the frame, which is at `column` of the box.
+    fn push_edge(&self, result: &mut String, c: char, column: usize, color: bool) {
+        if color {
+            color::push_colored(result, c, self.rainbow(column));
+            color::push_reset(result);
+        } else {
+            result.push(c);
+        }
+    }
+
+    /// Appends `prefix` in dim text, without the rainbow of the content.
+    fn push_prefix(&self, result: &mut String, prefix: &str, color: bool) {
+        if color {
+            color::push_dim(result, prefix);
+        } else {
+            result.push_str(prefix);
+        }
+    }
+
+    /// Appends `text`, which starts at `column` of the box.
+    fn push_content(&self, result: &mut String, text: &str, column: usize, color: bool) {
+        if color && self.rainbow_text && text.chars().any(|c| c != ' ') {
+            let mut column = column;
+            for c in text.chars() {
+                if c == ' ' {
+                    result.push(c);
+                } else {
+                    color::push_colored(result, c, self.rainbow(column));
+                }
+                column += self.char_width(c);
+            }
+            color::push_reset(result);
+        } else {
+            result.push_str(text);
+        }
+    }
+}
+
+/// Mask of [`PrettierPrinter::with_redact()`].
+const REDACTED: &str = "****";
+
+/// Replaces the values of `fields` in a `{:#?}` string with [`REDACTED`]. A value that spans
+/// several lines is replaced with a single mask.
+fn redact(debug_str: &str, fields: &[String]) -> String {
+    let mut result = String::new();
+    // Indent of the field whose value is being skipped
+    let mut skipping: Option<usize> = None;
+    for line in debug_str.lines() {
+        let content = line.trim_start_matches(' ');
+        let indent = line.len() - content.len();
+        if let Some(skip_indent) = skipping {
+            if indent == skip_indent {
+                skipping = None;
+                if content.ends_with(',') {
+                    result.push(',');
+                }
+                result.push('\n');
+            }
+            continue;
+        }
+
+        let field = content.split_once(": ").filter(|(key, _)| {
+            let key = key.trim_matches('"');
+            fields.iter().any(|field| field == key)
+        });
+        match field {
+            Some((key, value)) => {
+                result.push_str(&line[..indent]);
+                result.push_str(key);
+                result.push_str(": ");
+                result.push_str(REDACTED);
+                if value.ends_with(['[', '{', '(']) {
+                    skipping = Some(indent);
+                } else {
+                    if value.ends_with(',') {
+                        result.push(',');
+                    }
+                    result.push('\n');
+                }
+            }
+            None => {
+                result.push_str(line);
+                result.push('\n');
+            }
+        }
+    }
+    result
+}
+
+/// Returns true if the first and last lines of `s` are the edges of a frame. See
+/// [`PrettierPrinter::with_detect_existing_border()`].
+fn is_framed(s: &str) -> bool {
+    fn is_edge(line: &str) -> bool {
+        let chars: Vec<char> = line.trim_end().chars().collect();
+        chars.len() >= 2
+            && chars[1..chars.len() - 1]
+                .iter()
+                .all(|&c| matches!(c, ' ' | '-' | '=' | '\u{2500}'..='\u{257F}'))
+            && !chars[0].is_alphanumeric()
+            && !chars[0].is_whitespace()
+            && (chars[0] == chars[chars.len() - 1]
+                || ('\u{2500}'..='\u{257F}').contains(&chars[0])
+                    && ('\u{2500}'..='\u{257F}').contains(&chars[chars.len() - 1]))
+    }
+
+    let lines: Vec<&str> = s.trim_end_matches('\n').lines().collect();
+    lines.len() >= 2 && is_edge(lines[0]) && is_edge(lines[lines.len() - 1])
+}
+
+/// Short label that identifies `seed` in [`PrettierPrinter::contact_sheet()`].
+fn seed_label(seed: Seed) -> String {
+    let hex: String = seed[..4].iter().map(|b| format!("{:02x}", b)).collect();
+    format!("seed {}…", hex)
+}
+
+/// Mirrors each line of `text`, reversing the order of grapheme clusters so that multi-byte and
+/// combined characters stay intact. Each grapheme keeps the color it has in `text`. Lines are
+/// padded to the same width first so that the box stays aligned.
+fn flip_horizontal<F>(text: &str, display_width: F) -> String
+where
+    F: Fn(&str) -> usize,
+{
+    let lines: Vec<Vec<(char, Color)>> = text.lines().map(color::parse_colored).collect();
+    let plain: Vec<String> = lines
+        .iter()
+        .map(|line| line.iter().map(|&(c, _)| c).collect())
+        .collect();
+    let width = plain
+        .iter()
+        .map(|line| display_width(line))
+        .max()
+        .unwrap_or(0);
+    let mut result = String::new();
+    for (line, colored) in plain.iter().zip(&lines) {
+        // A grapheme gets the color of its first character
+        let mut colors = colored.iter().map(|&(_, color)| color);
+        let mut graphemes: Vec<(&str, Color)> = line
+            .graphemes(true)
+            .map(|grapheme| {
+                let color = colors.next().unwrap_or(Color::Reset);
+                colors
+                    .by_ref()
+                    .take(grapheme.chars().count() - 1)
+                    .for_each(drop);
+                (grapheme, color)
+            })
+            .collect();
+        graphemes.extend(repeat((" ", Color::Reset)).take(width - display_width(line)));
+        // Leading spaces would be trailing spaces after flipping
+        let start = graphemes.iter().take_while(|&&(g, _)| g == " ").count();
+
+        let mut current = Color::Reset;
+        for &(grapheme, color) in graphemes[start..].iter().rev() {
+            if grapheme != " " && color != current {
+                if color == Color::Reset {
+                    color::push_reset(&mut result);
+                    result.push_str(grapheme);
+                } else {
+                    let mut chars = grapheme.chars();
+                    color::push_colored(&mut result, chars.next().unwrap(), color);
+                    result.extend(chars);
+                }
+                current = color;
+            } else {
+                result.push_str(grapheme);
+            }
+        }
+        if current != Color::Reset {
+            color::push_reset(&mut result);
+        }
+        result.push('\n');
+    }
+    result
+}
+
+/// Seed for line `index` of a box decorated with `seed` in
+/// [`PrettierPrinter::with_decorrelated_lines()`].
+fn decorrelated_seed(seed: Seed, index: usize) -> Seed {
+    let mut bytes = seed.to_vec();
+    bytes.extend_from_slice(&(index as u64).to_le_bytes());
+    seed_from_bytes(&bytes)
+}
+
+/// [`PrettierConfig::widest_line()`] for printable ASCII.
+fn widest_line_ascii(s: &str) -> Option<usize> {
+    s.lines().map(str::len).max()
+}
+
+/// Returns an error if `stars` and `weights` cannot be used together. See
+/// [`PrettierPrinter::try_with_palette()`].
+fn check_palette(stars: &[char], weights: &[u16]) -> Result<(), PaletteError> {
+    if stars.len() != weights.len() {
+        return Err(PaletteError::LengthMismatch {
+            stars: stars.len(),
+            weights: weights.len(),
+        });
+    }
+    WeightedAliasIndex::new(weights.to_vec()).map_err(|e| match e {
+        WeightedError::NoItem => PaletteError::Empty,
+        WeightedError::AllWeightsZero => PaletteError::AllZero,
+        WeightedError::InvalidWeight | WeightedError::TooMany => PaletteError::TooLarge,
+    })?;
+    Ok(())
+}
+
+/// Width of a box that holds lines that are at most `content_width` columns wide.
+fn box_width(content_width: usize) -> usize {
+    content_width + content_width / 10 + 2
+}
+
+/// Random number generators that decide where stars go.
+struct StarRng<'config> {
+    rng: SmallRng,
+    line_rng: SmallRng,
+    star_rng: SmallRng,
+    line_distribution: Bernoulli,
+    stars: &'config [char],
+    star_distribution: WeightedAliasIndex<u16>,
+    /// Number of stars that can still be placed, or `None` if there is no limit.
+    remaining: Option<usize>,
+    trace: Trace,
+}
+
+/// A random decision made while decorating. See [`PrettierPrintDisplayer::output_trace()`].
+#[cfg(feature = "rng-trace")]
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum RngDraw {
+    /// Whether a line or a segment of a border gets a star.
+    Sparkle(bool),
+    /// Index of the chosen star in the palette.
+    Star(usize),
+    /// Column chosen for a star, relative to the start of the range it was chosen from.
+    Position(usize),
+    /// Result of a coin flip, such as for [`PrettierPrinter::with_density_per_column()`].
+    Chance(bool),
+}
+
+/// What [`StarRng`] draws from and records while decorating, and what it passes from line to
+/// line.
+#[derive(Debug, Default)]
+struct Trace {
+    #[cfg(feature = "rng-trace")]
+    source: DrawSource,
+    /// Row and column of each star that was placed, if they are being recorded.
+    stars: Option<Vec<(usize, usize, char)>>,
+    /// Row of the output that is being decorated.
+    row: usize,
+    /// Star of each line from [`DecorationPlan`], which replaces the random stars.
+    plan: Option<Vec<Option<(usize, char)>>>,
+    /// Dim text written before each line, such as from [`PrettierPrinter::with_breadcrumbs()`].
+    prefixes: Vec<String>,
+}
+
+/// Where [`StarRng`] gets its decisions from.
+#[cfg(feature = "rng-trace")]
+#[derive(Debug, Default)]
+enum DrawSource {
+    #[default]
+    Random,
+    /// Random, and each decision is recorded.
+    Record(Vec<RngDraw>),
+    /// Decisions are taken from a recording instead of being random.
+    Replay(VecDeque<RngDraw>),
+}
+
+#[cfg(feature = "rng-trace")]
+impl DrawSource {
+    /// Makes a decision with `random`, or takes the next recorded decision if replaying.
+    /// `record` and `replay` convert the decision to and from [`RngDraw`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the next recorded decision is missing or of another kind.
+    fn draw<T, R>(
+        &mut self,
+        random: R,
+        record: fn(T) -> RngDraw,
+        replay: fn(RngDraw) -> Option<T>,
+    ) -> T
+    where
+        T: Copy,
+        R: FnOnce() -> T,
+    {
+        match self {
+            DrawSource::Random => random(),
+            DrawSource::Record(draws) => {
+                let value = random();
+                draws.push(record(value));
+                value
+            }
+            DrawSource::Replay(draws) => draws
+                .pop_front()
+                .and_then(replay)
+                .expect("trace does not match the decorated string"),
+        }
+    }
+}
+
+/// Makes a decision with `$random`. With the `rng-trace` feature, the decision goes through
+/// [`DrawSource`] as a `RngDraw::$kind`, so that it can be recorded or replayed.
+macro_rules! draw {
+    ($trace:expr, $random:expr, $kind:ident) => {{
+        #[cfg(feature = "rng-trace")]
+        let value = $trace.source.draw(
+            || $random,
+            RngDraw::$kind,
+            |draw| match draw {
+                RngDraw::$kind(value) => Some(value),
+                _ => None,
+            },
+        );
+        #[cfg(not(feature = "rng-trace"))]
+        let value = $random;
+        value
+    }};
+}
+
+impl<'config> StarRng<'config> {
+    fn new(seed: Seed, config: &'config PrettierConfig) -> Self {
+        let mut rng = SmallRng::from_seed(seed);
+        let line_rng = SmallRng::from_seed(PrettierPrinter::gen_seed(&mut rng));
+        let star_rng = SmallRng::from_seed(PrettierPrinter::gen_seed(&mut rng));
+        Self {
+            rng,
+            line_rng,
+            star_rng,
+            line_distribution: Bernoulli::from_ratio(
+                config.sparkle_ratio.0,
+                config.sparkle_ratio.1,
+            )
+            .unwrap(), // Checked in with_sparkle_ratio() and try_with_config()
+            stars: &config.stars,
+            // Weights are checked in try_with_palette()
+            star_distribution: WeightedAliasIndex::new(config.weights.clone()).unwrap(),
+            remaining: config.max_total_stars,
+            trace: Trace::default(),
+        }
+    }
+
+    /// Returns true if a star should be added.
+    fn sparkle(&mut self) -> bool {
+        let (distribution, rng) = (&self.line_distribution, &mut self.line_rng);
+        draw!(self.trace, distribution.sample(rng), Sparkle)
+    }
+
+    fn star(&mut self) -> char {
+        let (distribution, rng) = (&self.star_distribution, &mut self.star_rng);
+        let index = draw!(self.trace, distribution.sample(rng), Star);
+        *self
+            .stars
+            .get(index)
+            .expect("trace has a star that is not in the palette")
+    }
+
+    fn position(&mut self, range: Range<usize>) -> usize {
+        let rng = &mut self.rng;
+        let start = range.start;
+        let offset = draw!(self.trace, rng.gen_range(range) - start, Position);
+        start + offset
+    }
+
+    /// Uses up one star from the limit. Returns false if there are no stars left.
+    fn take(&mut self) -> bool {
+        match &mut self.remaining {
+            Some(0) => false,
+            Some(n) => {
+                *n -= 1;
+                true
+            }
+            None => true,
+        }
+    }
+
+    /// Prefix of the current line, or an empty string if there is none.
+    fn prefix(&self) -> &str {
+        let line = self.trace.row.wrapping_sub(1); // Row 0 is the top border
+        self.trace.prefixes.get(line).map_or("", String::as_str)
+    }
+
+    /// The planned star of the current line, or `None` if there is no plan. Lines past the end of
+    /// the plan have no star.
+    fn planned(&self) -> Option<Option<(usize, char)>> {
+        // Row 0 is the top border
+        let line = self.trace.row.checked_sub(1)?;
+        let plan = self.trace.plan.as_ref()?;
+        Some(plan.get(line).copied().flatten())
+    }
+
+    /// Records that `star` was placed at `column` of the current row.
+    fn place(&mut self, column: usize, star: char) {
+        if let Some(stars) = &mut self.trace.stars {
+            stars.push((self.trace.row, column, star));
+        }
+    }
+
+    /// Returns true with probability `p`.
+    fn chance(&mut self, p: f64) -> bool {
+        let rng = &mut self.rng;
+        draw!(self.trace, rng.gen_bool(p), Chance)
+    }
+}
+
+impl<T> PrettierPrintDisplayer<'_, T>
+where
+    T: Debug,
+{
+    /// Writes the output to `w`. See [`PrettierPrinter::with_color()`] for when colors are
+    /// written.
+    pub fn output_to<W>(&self, w: &mut W) -> io::Result<()>
+    where
+        W: io::Write + IsTty,
+    {
+        self.config
+            .output_to(self.seed, &self.config.debug_string(self.inner), w)
+    }
+
+    /// Returns the output. Same as `to_string()`.
+    pub fn render(&self) -> String {
+        self.config
+            .output(self.seed, &self.config.debug_string(self.inner))
+    }
+
+    /// Returns true if the output fits in a terminal of `terminal_size` columns and rows without
+    /// wrapping or scrolling. The output ends with a line break, so it needs one more row than it
+    /// has lines. See also [`PrettierPrintDisplayer::measure()`].
+    pub fn fits(&self, terminal_size: (u16, u16)) -> bool {
+        let (width, height) = self
+            .config
+            .measure(self.seed, &self.config.debug_string(self.inner));
+        width <= terminal_size.0 as usize && height < terminal_size.1 as usize
+    }
+}
+
+impl<T> Display for PrettierPrintDisplayer<'_, T>
+where
+    T: Debug,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.config
+            .output_fmt(self.seed, &self.config.debug_string(self.inner), f)
+    }
+}
+
+/// Like `PrettierPrintDisplayer`, but holds text that was already formatted by one of the
+/// `PrettierPrinter::print_*()` methods.
+#[derive(Debug, Clone)]
+pub struct PrettierPrintText {
+    seed: Seed,
+    text: String,
+    config: PrettierConfig,
+}
+
+impl PrettierPrintText {
+    /// Returns the output. Same as `to_string()`.
+    pub fn render(&self) -> String {
+        self.config.output(self.seed, &self.text)
+    }
+
+    #[cfg(feature = "tracing-subscriber")]
+    pub(crate) fn set_border(&mut self, border: char) {
+        self.config.border = border;
+    }
+
+    /// Writes the output to `w`. See [`PrettierPrinter::with_color()`] for when colors are
+    /// written.
+    pub fn output_to<W>(&self, w: &mut W) -> io::Result<()>
+    where
+        W: io::Write + IsTty,
+    {
+        self.config.output_to(self.seed, &self.text, w)
+    }
+}
+
+impl Display for PrettierPrintText {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.config.output_fmt(self.seed, &self.text, f)
+    }
+}
+
+/// Derives a `Seed` from `bytes` using a stable hash. Each 8-byte chunk of the seed is the 64-bit
+/// FNV-1a hash of `bytes` followed by the chunk's index as a single byte, in little-endian order.
+/// Unlike `std::hash::Hasher`, the result never changes between platforms or Rust versions.
+pub fn seed_from_bytes(bytes: &[u8]) -> Seed {
+    let mut seed = Seed::default();
+    for (i, chunk) in seed.chunks_mut(8).enumerate() {
+        let hash = fnv1a(bytes.iter().copied().chain(once(i as u8)));
+        chunk.copy_from_slice(&hash.to_le_bytes()[..chunk.len()]);
+    }
+    seed
+}
+
+fn fnv1a<I>(bytes: I) -> u64
+where
+    I: IntoIterator<Item = u8>,
+{
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+    bytes.into_iter().fold(OFFSET_BASIS, |hash, b| {
+        (hash ^ b as u64).wrapping_mul(PRIME)
+    })
+}
+
+fn hex_dump(bytes: &[u8], bytes_per_row: usize) -> String {
+    let lines: Vec<String> = bytes
+        .chunks(bytes_per_row)
+        .enumerate()
+        .map(|(i, row)| {
+            let hex: Vec<String> = row.iter().map(|b| format!("{:02x}", b)).collect();
+            let ascii: String = row
+                .iter()
+                .map(|&b| {
+                    if b.is_ascii_graphic() || b == b' ' {
+                        b as char
+                    } else {
+                        '.'
+                    }
+                })
+                .collect();
+            format!(
+                "{:08x}  {:width$}  |{}|",
+                i * bytes_per_row,
+                hex.join(" "),
+                ascii,
+                width = bytes_per_row * 3 - 1
+            )
+        })
+        .collect();
+    lines.join("\n")
+}
+
+fn grid_string<T>(rows: &[Vec<T>]) -> String
+where
+    T: Debug,
+{
+    let cells: Vec<Vec<String>> = rows
+        .iter()
+        .map(|row| row.iter().map(|cell| format!("{:?}", cell)).collect())
+        .collect();
+    let column_count = cells.iter().map(|row| row.len()).max().unwrap_or(0);
+    let column_widths: Vec<usize> = (0..column_count)
+        .map(|column| {
+            cells
+                .iter()
+                .filter_map(|row| row.get(column))
+                .map(|cell| cell.chars().count())
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+
+    let lines: Vec<String> = cells
+        .iter()
+        .map(|row| {
+            let line: Vec<String> = (0..column_count)
+                .map(|column| {
+                    let cell = row.get(column).map_or("", |s| s.as_str());
+                    format!("{:>width$}", cell, width = column_widths[column])
+                })
+                .collect();
+            line.join("  ").trim_end().to_string()
+        })
+        .collect();
+    lines.join("\n")
+}
+
+fn map_string<I, K, V>(map: I, sorted: bool) -> String
+where
+    I: IntoIterator<Item = (K, V)>,
+    K: Debug,
+    V: Debug,
+{
+    let mut entries: Vec<(String, String)> = map
+        .into_iter()
+        .map(|(key, value)| (format!("{:?}", key), format!("{:?}", value)))
+        .collect();
+    if sorted {
+        entries.sort();
+    }
+    let key_width = entries
+        .iter()
+        .map(|(key, _)| key.chars().count())
+        .max()
+        .unwrap_or(0);
+
+    let lines: Vec<String> = entries
+        .iter()
+        .map(|(key, value)| format!("{:<width$}: {}", key, value, width = key_width))
+        .collect();
+    lines.join("\n")
+}
+
+fn kv_string<F>(pairs: &[(&str, &dyn Debug)], display_width: F) -> String
+where
+    F: Fn(&str) -> usize,
+{
+    let key_width = pairs
+        .iter()
+        .map(|(key, _)| display_width(key))
+        .max()
+        .unwrap_or(0);
+
+    let lines: Vec<String> = pairs
+        .iter()
+        .map(|(key, value)| {
+            let padding = " ".repeat(key_width - display_width(key));
+            format!("{}{} = {:?}", padding, key, value)
+        })
+        .collect();
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+    use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+    #[test]
+    fn prettier_printer() {
+        let seed = {
+            let mut seed = Seed::default();
+            seed[0] = 180; // Good seed for example
+            seed
+        };
+        {
+            let result = PrettierPrinter::new_with_seed(seed).print(&0).to_string();
+            let expected = r"🌈 🌈
+ 0
+🌈 🌈
+";
+            assert_eq!(result, expected);
+        }
+        {
+            #[derive(Debug, Clone)]
+            #[allow(dead_code)]
+            struct Type {
+                a: String,
+                b: Vec<i32>,
+                c: HashMap<&'static str, &'static str>,
+            }
+
+            let input = Type {
+                a: "a".to_string(),
+                b: vec![0, 1],
+                c: {
+                    let mut map = HashMap::new();
+                    map.insert("So", "pretty");
+                    map
+                },
+            };
+
+            let displayer = PrettierPrinter::new_with_seed(seed).print(&input);
+
+            let result = displayer.to_string();
+            let expected = r#"🌈                         🌈
+ Type {
+ ⭐   a: "a",
+   ⭐ b: [    ⭐
+      🦀  0,
+        ⭐1,    ⭐
+  🌟  ],
+   ⭐ c: {
+ ⭐       "So": "pretty",   ⭐
+   ⭐ },       ⭐
+ }             ☀
+🌈                         🌈
+"#;
+            assert_eq!(result, expected);
+            // Check if cloned Displayer outputs the same string
+            assert_eq!(displayer.clone().to_string(), expected);
+
+            println!("\n{:#?}\n\n", &input);
+            println!("{}\n", result);
+        }
+    }
+
+    #[test]
+    fn emoji_width() {
+        // Width of a border line when the terminal renders emojis with `emoji_width` columns
+        fn border_width(output: &str, emoji_width: usize) -> usize {
+            let border = output.lines().next().unwrap();
+            border.chars().filter(|&c| c == ' ').count() + 2 * emoji_width
+        }
+
+        let input = vec![1, 2, 3];
+        let seed = Seed::default();
+        let wide = PrettierPrinter::new_with_seed(seed)
+            .with_emoji_width(2)
+            .print(&input)
+            .to_string();
+        let narrow = PrettierPrinter::new_with_seed(seed)
+            .with_emoji_width(1)
+            .print(&input)
+            .to_string();
+        assert_eq!(border_width(&narrow, 1) + 1, border_width(&wide, 2));
+        // A star is not placed in an indent that is narrower than the star
+        let config = PrettierConfig {
+            sparkle_ratio: (1, 1),
+            sized_stars: true,
+            ..PrettierConfig::default()
+        };
+        for seed in 0..10 {
+            let result = config.output([seed; 32], " a\nbbbb");
+            assert!(
+                result.lines().nth(1).unwrap().starts_with("  a"),
+                "{}",
+                result
+            );
+        }
+        assert_eq!(
+            PrettierPrinter::new_with_seed(seed)
+                .try_with_emoji_width(3)
+                .err(),
+            Some(EmojiWidthError { emoji_width: 3 })
+        );
+        // Leading stars take the place of as many spaces as they are wide
+        let debug_str = format!("{:#?}", input);
+        for &(output, emoji_width) in &[(&narrow, 1), (&wide, 2)] {
+            for (line, debug_line) in output.lines().skip(1).zip(debug_str.lines()) {
+                let content = debug_line.trim_start();
+                let start = line.find(content).unwrap();
+                assert_eq!(
+                    display_width(&line[..start], emoji_width),
+                    1 + debug_line.len() - content.len(),
+                    "{}",
+                    output
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn fnv1a() {
+        // Test vectors from the FNV reference implementation
+        assert_eq!(super::fnv1a(*b""), 0xcbf29ce484222325);
+        assert_eq!(super::fnv1a(*b"a"), 0xaf63dc4c8601ec8c);
+        assert_eq!(super::fnv1a(*b"foobar"), 0x85944171f73967e8);
+    }
+
+    #[test]
+    fn print_diff() {
+        #[derive(Debug)]
+        #[allow(dead_code)]
+        struct State {
+            name: &'static str,
+            count: u32,
+        }
+
+        let before = State {
+            name: "a",
+            count: 1,
+        };
+        let after = State {
+            name: "a",
+            count: 2,
+        };
+        let result = PrettierPrinter::default()
+            .print_diff(&before, &after)
+            .to_string();
+        let marked = |marker: &str| {
+            result
+                .lines()
+                .filter(|line| line.contains(marker))
+                .map(|line| line.trim_start_matches(' ').to_string())
+                .collect::<Vec<String>>()
+        };
+        assert_eq!(marked(DIFF_REMOVED).len(), 1);
+        assert!(marked(DIFF_REMOVED)[0].contains("count: 1,"));
+        assert_eq!(marked(DIFF_ADDED).len(), 1);
+        assert!(marked(DIFF_ADDED)[0].contains("count: 2,"));
+        assert!(result.contains("name: \"a\","));
+    }
+
+    #[test]
+    fn mix_seeds() {
+        let a = seed_from_bytes(b"a");
+        let b = seed_from_bytes(b"b");
+        assert_eq!(
+            PrettierPrinter::mix_seeds(a, b),
+            PrettierPrinter::mix_seeds(a, b)
+        );
+        assert_ne!(
+            PrettierPrinter::mix_seeds(a, b),
+            PrettierPrinter::mix_seeds(b, a)
+        );
+        assert_ne!(PrettierPrinter::mix_seeds(a, b), a);
+        assert_ne!(PrettierPrinter::mix_seeds(a, a), Seed::default());
+    }
+
+    #[test]
+    fn from_label() {
+        let input = vec![1, 2, 3];
+        let render = |label| PrettierPrinter::from_label(label).print(&input).to_string();
+        assert_eq!(render("alice"), render("alice"));
+        assert_ne!(render("alice"), render("bob"));
+        assert_ne!(seed_from_bytes(b"alice"), seed_from_bytes(b"bob"));
+    }
+
+    #[test]
+    fn star_location_border() {
+        let input: Vec<String> = (0..10).map(|i| "a".repeat(i * 4)).collect();
+        for seed in 0..10 {
+            let result = PrettierPrinter::new_with_seed([seed; 32])
+                .with_star_location(StarLocation::Border)
+                .print(&input)
+                .to_string();
+            let lines: Vec<&str> = result.lines().collect();
+            let (first, last) = (lines[0], lines[lines.len() - 1]);
+            assert!(first.chars().any(|c| DEFAULT_STARS.contains(&c)));
+            assert!(last.chars().any(|c| DEFAULT_STARS.contains(&c)));
+            // Border width is unaffected by stars
+            assert_eq!(first.chars().count(), last.chars().count());
+
+            let content = &lines[1..lines.len() - 1];
+            assert_eq!(
+                content.join("\n"),
+                format!(" {:#?}", input).replace('\n', "\n ")
+            );
+        }
+    }
+
+    #[test]
+    fn line_seeds() {
+        let seeds = ([1; 32], [2; 32]);
+        let render = |line_seeds| {
+            let config = PrettierConfig {
+                line_seeds,
+                ..PrettierConfig::default()
+            };
+            config.output(Seed::default(), "    a\n    a\n    a")
+        };
+        let forward = render(vec![seeds.0, seeds.1]);
+        let forward: Vec<&str> = forward.lines().collect();
+        let reverse = render(vec![seeds.1, seeds.0]);
+        let reverse: Vec<&str> = reverse.lines().collect();
+
+        assert_ne!(forward[1], forward[2]);
+        assert_eq!(forward[1], reverse[2]);
+        assert_eq!(forward[2], reverse[1]);
+        // Seeds are reused for the third line
+        assert_eq!(forward[1], forward[3]);
+        assert_eq!(reverse[1], reverse[3]);
+    }
+
+    #[test]
+    fn fits() {
+        let input = vec![1, 2, 3];
+        let mut printer = PrettierPrinter::new_with_seed([0; 32]);
+        let displayer = printer.print(&input);
+        let output = displayer.to_string();
+        let width = output.lines().map(|line| display_width(line, 2)).max();
+        let height = output.lines().count();
+        assert_eq!(
+            PrettierPrintDisplayer::<()>::measure([0; 32], &format!("{:#?}", input)),
+            (width.unwrap(), height)
+        );
+
+        let (width, height) = (width.unwrap() as u16, height as u16);
+        assert!(displayer.fits((width, height + 1)));
+        assert!(displayer.fits((80, 24)));
+        assert!(!displayer.fits((width - 1, height + 1)));
+        assert!(!displayer.fits((width, height)));
+    }
+
+    #[test]
+    fn render() {
+        let mut printer = PrettierPrinter::default();
+        let value = vec![1, 2];
+        let displayer = printer.print(&value);
+        assert_eq!(displayer.render(), displayer.to_string());
+        let text = printer.print_display(&"a\nb");
+        assert_eq!(text.render(), text.to_string());
+    }
+
+    #[test]
+    fn output_fmt() {
+        let seed = [3; 32];
+        let debug_str = format!("{:#?}", vec![vec![1, 2], vec![3]]);
+        let mut result = String::new();
+        PrettierPrintDisplayer::<()>::output_fmt(seed, &debug_str, &mut result).unwrap();
+        assert_eq!(
+            result,
+            PrettierPrintDisplayer::<()>::output(seed, &debug_str)
+        );
+    }
+
+    #[test]
+    fn rainbow_text() {
+        #[derive(Debug)]
+        #[allow(dead_code)]
+        struct Type {
+            a: Vec<i32>,
+            b: &'static str,
+        }
+
+        let input = Type {
+            a: vec![1, 2],
+            b: "ab",
+        };
+        for seed in 0..10 {
+            let plain = PrettierPrinter::new_with_seed([seed; 32])
+                .print(&input)
+                .to_string();
+            let colored = PrettierPrinter::new_with_seed([seed; 32])
+                .with_rainbow_text(true)
+                .print(&input)
+                .to_string();
+            assert_ne!(colored, plain);
+            assert_eq!(color::strip_ansi(&colored), plain);
+        }
+
+        let config = PrettierConfig {
+            rainbow_text: true,
+            ..PrettierConfig::default()
+        };
+        let mut expected = String::new();
+        color::push_colored(&mut expected, 'a', color::rainbow(1));
+        color::push_colored(&mut expected, 'b', color::rainbow(2));
+        assert_ne!(color::rainbow(1), color::rainbow(2));
+        assert!(config.output(Seed::default(), "ab").contains(&expected));
+
+        // Wide characters take up two columns of the gradient
+        let mut expected = String::new();
+        color::push_colored(&mut expected, '日', color::rainbow(1));
+        color::push_colored(&mut expected, '本', color::rainbow(3));
+        color::push_colored(&mut expected, 'a', color::rainbow(5));
+        assert!(config.output(Seed::default(), "日本a").contains(&expected));
+    }
+
+    #[test]
+    fn default_consts() {
+        const SPARKLES: [char; 4] = {
+            let mut stars = DEFAULT_STARS;
+            stars[0] = '✨';
+            stars
+        };
+        const WEIGHTS: [u16; 4] = [DEFAULT_WEIGHTS[0], 0, 0, 0];
+
+        let input = vec![vec![1, 2], vec![3, 4], vec![5, 6]];
+        let result = PrettierPrinter::new_with_seed(Seed::default())
+            .with_border(DEFAULT_BORDER)
+            .with_palette(&SPARKLES, &WEIGHTS)
+            .print(&input)
+            .to_string();
+        assert!(result.starts_with(DEFAULT_BORDER));
+        assert!(result.contains('✨'));
+        assert!(!result.chars().any(|c| DEFAULT_STARS.contains(&c)));
+    }
+
+    #[rstest]
+    #[case(&['a', 'b'], &[1], PaletteError::LengthMismatch { stars: 2, weights: 1 })]
+    #[case(&[], &[], PaletteError::Empty)]
+    #[case(&['a', 'b'], &[0, 0], PaletteError::AllZero)]
+    #[case(&['a', 'b'], &[u16::MAX, 1], PaletteError::TooLarge)]
+    fn try_with_palette(
+        #[case] stars: &[char],
+        #[case] weights: &[u16],
+        #[case] expected: PaletteError,
+    ) {
+        let result = PrettierPrinter::default().try_with_palette(stars, weights);
+        assert_eq!(result.unwrap_err(), expected);
+        assert!(!expected.to_string().is_empty());
+        assert!(PrettierPrinter::default()
+            .try_with_palette(&['a', 'b'], &[0, 1])
+            .is_ok());
+    }
+
+    #[test]
+    #[should_panic(expected = "at least one weight must be non-zero")]
+    fn with_palette_all_zero() {
+        let _ = PrettierPrinter::default().with_palette(&['a'], &[0]);
+    }
+
+    #[test]
+    #[should_panic(expected = "sum of weights must fit in a u16")]
+    fn with_palette_too_large() {
+        let _ = PrettierPrinter::default().with_palette(&['a', 'b'], &[u16::MAX, 1]);
+    }
+
+    #[test]
+    fn contact_sheet() {
+        let seeds = [[1; 32], [2; 32], [3; 32], [4; 32]];
+        let printer = PrettierPrinter::default();
+        let result = printer.contact_sheet(&vec![1, 2], &seeds, 2);
+        let lines: Vec<&str> = result.lines().collect();
+
+        let box_height = format!("{:#?}", vec![1, 2]).lines().count() + 2;
+        assert_eq!(lines.len(), 2 * (box_height + 1));
+        for (row, pair) in seeds.chunks(2).enumerate() {
+            let label_line = lines[row * (box_height + 1) + box_height];
+            let first = label_line.find(&seed_label(pair[0])).unwrap();
+            let second = label_line.find(&seed_label(pair[1])).unwrap();
+            assert!(first < second);
+
+            let top = lines[row * (box_height + 1)];
+            assert_eq!(top.matches(DEFAULT_BORDER).count(), 4);
+        }
+    }
+
+    #[test]
+    fn print_with_width_hint() {
+        let input = vec![1, 2, 3];
+        let measured = PrettierPrinter::new_with_seed([0; 32])
+            .print(&input)
+            .to_string();
+        let hinted = PrettierPrinter::new_with_seed([0; 32])
+            .print_with_width_hint(&input, 6)
+            .to_string();
+        assert_eq!(hinted, measured);
+
+        let hinted = PrettierPrinter::new_with_seed([0; 32])
+            .print_with_width_hint(&input, 20)
+            .to_string();
+        let lines: Vec<&str> = hinted.lines().collect();
+        assert_eq!(display_width(lines[0], 2), box_width(20) + 2);
+
+        let hinted = PrettierPrinter::new_with_seed([0; 32])
+            .print_with_width_hint(&input, 3)
+            .to_string();
+        // "    1," is cut down to three spaces
+        assert!(hinted.contains('['));
+        assert!(!hinted.contains('1'));
+    }
+
+    #[test]
+    fn indent() {
+        let config = PrettierConfig {
+            indent: 4,
+            ..PrettierConfig::default()
+        };
+        let debug_str = "a\n\n  b\n";
+        for seed in 0..10 {
+            let result = config.output([seed; 32], debug_str);
+            let lines: Vec<&str> = result.lines().collect();
+            assert_eq!(lines.len(), 5);
+            for line in lines {
+                assert!(line.starts_with("    "));
+            }
+            let unindented = PrettierConfig::default().output([seed; 32], debug_str);
+            for (line, expected) in result.lines().zip(unindented.lines()) {
+                assert_eq!(&line[4..], expected);
+            }
+        }
+    }
+
+    #[test]
+    fn collection_summary() {
+        let input: Vec<u32> = (0..100).collect();
+        let result = PrettierPrinter::default()
+            .with_collection_summary(3)
+            .print(&input)
+            .to_string();
+        let lines: Vec<&str> = result.lines().collect();
+        assert_eq!(lines.len(), 6 + 2);
+        assert!(lines[4].contains("2,"));
+        assert!(lines[5].contains(".. 97 more items .."));
+        assert!(!result.contains("99"));
+    }
+
+    #[rstest]
+    #[case(PrettierPrinter::default())]
+    #[case(PrettierPrinter::default().with_frame_rainbow(true))]
+    #[case(PrettierPrinter::default().with_frame_rainbow(true).with_rainbow_text(true))]
+    #[case(PrettierPrinter::default().with_density_per_column(0.5))]
+    #[case(PrettierPrinter::default().with_emoji_width(1).with_sparkle_ratio(1, 1))]
+    fn push_line_width(#[case] printer: PrettierPrinter) {
+        let config = printer.config;
+        for seed in 0..20 {
+            let mut stars = StarRng::new([seed; 32], &config);
+            for line in ["", "a", "  x", "    日本", "  abc  "] {
+                let mut result = String::new();
+                let used = config.push_line(&mut result, line, 10, &mut stars, true);
+                let plain = color::strip_ansi(&result);
+                assert_eq!(
+                    used,
+                    config.display_width(plain.trim_end_matches('\n')),
+                    "{:?}",
+                    plain
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn frame_rainbow() {
+        let config = PrettierConfig {
+            frame_rainbow: true,
+            ..PrettierConfig::default()
+        };
+        let debug_str = format!("{:#?}", vec![1, 2, 3]);
+        for seed in 0..10 {
+            let mut result = String::new();
+            config
+                .render([seed; 32], &debug_str, &mut result, true)
+                .unwrap();
+            assert_ne!(result, color::strip_ansi(&result));
+            let result = color::strip_ansi(&result);
+            let lines: Vec<&str> = result.lines().collect();
+            let width = display_width(lines[0], 2);
+            for border in [lines[0], lines[lines.len() - 1]] {
+                assert!(border.starts_with(DEFAULT_BORDER) && border.ends_with(DEFAULT_BORDER));
+                let edge = border.trim_matches(DEFAULT_BORDER);
+                assert!(edge.chars().all(|c| c == FRAME_HORIZONTAL));
+            }
+            for line in &lines[1..lines.len() - 1] {
+                assert!(line.starts_with(FRAME_VERTICAL) && line.ends_with(FRAME_VERTICAL));
+                assert_eq!(display_width(line, 2), width);
+            }
+        }
+    }
+
+    #[test]
+    fn with_intensity() {
+        let debug_str = format!("{:#?}", vec![1, 2, 3]);
+        let render = |intensity| {
+            PrettierPrinter::default()
+                .with_frame_rainbow(true)
+                .with_rainbow_text(true)
+                .with_intensity(intensity)
+                .config
+                .output([0; 32], &debug_str)
+        };
+        let brightness = |output: &str| -> u32 {
+            color::parse_colored(output)
+                .into_iter()
+                .map(|(_, color)| match color {
+                    Color::Rgb { r, g, b } => r as u32 + g as u32 + b as u32,
+                    _ => 0,
+                })
+                .sum()
+        };
+
+        let dark = render(0.0);
+        assert_eq!(dark, color::strip_ansi(&dark));
+        let dim = render(0.5);
+        let bright = render(1.0);
+        assert_eq!(color::strip_ansi(&dim), dark);
+        assert_eq!(color::strip_ansi(&bright), dark);
+        assert!(0 < brightness(&dim) && brightness(&dim) < brightness(&bright));
+    }
+
+    #[test]
+    fn with_line_transform() {
+        let result = PrettierPrinter::new_with_seed([0; 32])
+            .with_color(ColorChoice::Never)
+            .with_line_transform(|line| line.to_uppercase() + "!!")
+            .print_display(&"abc\nde")
+            .to_string();
+        let lines: Vec<&str> = result.lines().collect();
+        assert_eq!(lines.len(), 4);
+        assert!(lines[1].contains("ABC!!"));
+        assert!(lines[2].contains("DE!!"));
+        assert!(!result.contains("abc"));
+        assert_eq!(display_width(lines[0], 2), box_width(5) + 2);
+    }
+
+    #[test]
+    fn with_redact() {
+        #[derive(Debug)]
+        #[allow(dead_code)]
+        struct Login {
+            user: &'static str,
+            password: &'static str,
+            token: Vec<u8>,
+        }
+
+        let login = Login {
+            user: "ferris",
+            password: "hunter2",
+            token: vec![1, 2],
+        };
+        let result = PrettierPrinter::new_with_seed([0; 32])
+            .with_color(ColorChoice::Never)
+            .with_redact(vec!["password".to_string(), "token".to_string()])
+            .print(&login)
+            .to_string();
+        assert!(result.contains("user: \"ferris\","));
+        assert!(result.contains("password: ****,"));
+        assert!(result.contains("token: ****,"));
+        assert!(!result.contains("hunter2"));
+        assert_eq!(result.lines().count(), 7);
+
+        let map: std::collections::BTreeMap<_, _> = [("password", "hunter2")].into();
+        assert_eq!(
+            redact(&format!("{:#?}", map), &["password".to_string()]),
+            "{\n    \"password\": ****,\n}\n"
+        );
+    }
+
+    #[test]
+    fn with_interior_margin() {
+        let (left, right) = (2, 3);
+        let debug_str = format!("{:#?}", vec![1, 22, 333]);
+        for seed in 0..20 {
+            let result = PrettierPrinter::new_with_seed([seed; 32])
+                .with_color(ColorChoice::Never)
+                .with_frame_rainbow(true)
+                .with_interior_margin(left, right)
+                .print_display(&debug_str)
+                .to_string();
+            let lines: Vec<&str> = result.lines().collect();
+            let width = display_width(lines[0], 2);
+            assert_eq!(width, box_width(8) + left + right + 2);
+            for line in &lines[1..lines.len() - 1] {
+                let is_content = |c: char| c.is_ascii() && c != ' ';
+                let start = line.find(is_content).unwrap();
+                let end = line.rfind(is_content).unwrap() + 1;
+                assert!(display_width(&line[..start], 2) > left, "{}", result);
+                assert!(display_width(&line[end..], 2) > right, "{}", result);
+                assert!(line.ends_with(FRAME_VERTICAL));
+            }
+        }
+    }
+
+    #[test]
+    fn with_config() {
+        let config = PrettierConfig {
+            border: '+',
+            stars: vec!['*'],
+            weights: vec![1],
+            sparkle_ratio: (1, 1),
+            color: ColorChoice::Never,
+            emoji_width: 1,
+            ..PrettierConfig::default()
+        };
+        let render = || {
+            PrettierPrinter::new_with_seed([0; 32])
+                .with_config(config.clone())
+                .print_display(&"abc")
+                .to_string()
+        };
+        let result = render();
+        assert_eq!(result, render());
+        let lines: Vec<&str> = result.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0], "+    +");
+        assert!(lines[1].contains("abc") && lines[1].contains('*'));
+        assert_eq!(lines[2], "+    +");
+    }
+
+    #[rstest]
+    #[case(PrettierConfig { sparkle_ratio: (1, 0), ..PrettierConfig::default() }, ConfigError::SparkleRatio(1, 0))]
+    #[case(PrettierConfig { sparkle_ratio: (2, 1), ..PrettierConfig::default() }, ConfigError::SparkleRatio(2, 1))]
+    #[case(PrettierConfig { emoji_width: 3, ..PrettierConfig::default() }, ConfigError::EmojiWidth(EmojiWidthError { emoji_width: 3 }))]
+    #[case(PrettierConfig { column_split: 0, ..PrettierConfig::default() }, ConfigError::ColumnSplit)]
+    #[case(PrettierConfig { bytes_per_row: 0, ..PrettierConfig::default() }, ConfigError::BytesPerRow)]
+    #[case(PrettierConfig { density_per_column: Some(1.5), ..PrettierConfig::default() }, ConfigError::DensityPerColumn(1.5))]
+    #[case(PrettierConfig { star_density: Some(-1.0), ..PrettierConfig::default() }, ConfigError::StarDensity(-1.0))]
+    #[case(PrettierConfig { intensity: 2.0, ..PrettierConfig::default() }, ConfigError::Intensity(2.0))]
+    #[case(PrettierConfig { weights: vec![1], ..PrettierConfig::default() }, ConfigError::Palette(PaletteError::LengthMismatch { stars: 4, weights: 1 }))]
+    fn try_with_config(#[case] config: PrettierConfig, #[case] expected: ConfigError) {
+        let result = PrettierPrinter::new_with_seed([0; 32]).try_with_config(config);
+        assert_eq!(result.err(), Some(expected));
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid ratio 1/0")]
+    fn with_config_invalid() {
+        PrettierPrinter::new_with_seed([0; 32]).with_config(PrettierConfig {
+            sparkle_ratio: (1, 0),
+            ..PrettierConfig::default()
+        });
+    }
+
+    #[rstest]
+    #[case("")]
+    #[case("a")]
+    #[case("[\n    1,\n    22,\n]")]
+    #[case("\n\nabc \n")]
+    #[case("~!@#$%^&*()_+{}|:\"<>?`-=[]\\;',./")]
+    fn widest_line_ascii(#[case] s: &str) {
+        assert!(is_printable_ascii(s));
+        let config = PrettierConfig::default();
+        let unicode = s.lines().map(|line| display_width(line, 2)).max();
+        assert_eq!(super::widest_line_ascii(s), unicode);
+        assert_eq!(config.widest_line(s), unicode);
+
+        // Same string with a character that is not ASCII, which takes the other path
+        let wide = format!("{}é", s);
+        assert_eq!(
+            config.output([0; 32], &wide).replace('é', "e"),
+            config.output([0; 32], &format!("{}e", s))
+        );
+    }
+
+    #[test]
+    fn decorate_edge_wide_star() {
+        // The stars are two columns wide, and each edge has one column between the corners
+        let framed = "┌─┐\n│a│\n└─┘";
+        for seed in 0..10 {
+            let result = PrettierPrinter::new_with_seed([seed; 32])
+                .with_emoji_width(1)
+                .with_palette(&['日'], &[1])
+                .with_sparkle_ratio(1, 1)
+                .with_detect_existing_border(true)
+                .print_display(&framed)
+                .to_string();
+            assert_eq!(result, "┌─┐\n│a│\n└─┘\n");
+        }
+    }
+
+    #[test]
+    fn with_detect_existing_border() {
+        let framed = "┌──────────────────┐\n│ already framed   │\n└──────────────────┘";
+        let mut star_count = 0;
+        for seed in 0..10 {
+            let result = PrettierPrinter::new_with_seed([seed; 32])
+                .with_color(ColorChoice::Never)
+                .with_detect_existing_border(true)
+                .print_display(&framed)
+                .to_string();
+            let lines: Vec<&str> = result.lines().collect();
+            assert_eq!(lines.len(), 3, "{}", result);
+            assert!(lines[0].starts_with('┌') && lines[0].ends_with('┐'));
+            assert_eq!(lines[1], "│ already framed   │");
+            assert!(lines[2].starts_with('└') && lines[2].ends_with('┘'));
+            for edge in [lines[0], lines[2]] {
+                assert_eq!(display_width(edge, 2), 20);
+                star_count += edge.chars().filter(|c| DEFAULT_STARS.contains(c)).count();
+            }
+        }
+        assert!(star_count > 0);
+
+        assert!(is_framed("+--+\n|a |\n+--+\n"));
+        assert!(!is_framed("[\n    1,\n]"));
+        assert!(!is_framed("┌──┐"));
+        let unframed = PrettierPrinter::new_with_seed([0; 32])
+            .with_detect_existing_border(true)
+            .print_display(&"[\n    1,\n]")
+            .to_string();
+        assert_eq!(unframed.lines().count(), 5);
+    }
+
+    #[test]
+    fn print_sorted() {
+        let render = || {
+            let map: HashMap<String, HashSet<i32>> = (0..20)
+                .map(|i| {
+                    (
+                        format!("key {:02}", i),
+                        (0..5).map(|n| n * (i % 3)).collect(),
+                    )
+                })
+                .collect();
+            PrettierPrinter::new_with_seed([0; 32])
+                .print_sorted(&map)
+                .to_string()
+        };
+        let result = render();
+        assert_eq!(result, render());
+
+        let sorted: BTreeMap<String, BTreeSet<i32>> = (0..20)
+            .map(|i| {
+                (
+                    format!("key {:02}", i),
+                    (0..5).map(|n| n * (i % 3)).collect(),
+                )
+            })
+            .collect();
+        let expected = PrettierPrinter::new_with_seed([0; 32])
+            .print(&sorted)
+            .to_string();
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn with_width_fn() {
+        let debug_str = "日本語\n🦀🦀";
+        let result = PrettierPrinter::new_with_seed([0; 32])
+            .with_color(ColorChoice::Never)
+            .with_width_fn(|_| 1)
+            .print_display(&debug_str)
+            .to_string();
+        let lines: Vec<&str> = result.lines().collect();
+        assert_eq!(lines.len(), 4);
+        let width = box_width(3);
+        for border in [lines[0], lines[3]] {
+            assert_eq!(border.chars().count(), width + 1);
+        }
+        for line in &lines[1..3] {
+            assert!(line.chars().count() <= width + 1);
+        }
+    }
+
+    #[rstest]
+    #[case(PrettierPrinter::default())]
+    #[case(PrettierPrinter::default().with_frame_rainbow(true))]
+    #[case(PrettierPrinter::default().with_flip(Flip::Horizontal))]
+    #[case(PrettierPrinter::default().with_density_per_column(0.5))]
+    #[case(PrettierPrinter::default().with_star_location(StarLocation::Border))]
+    fn with_width_fn_zero(#[case] printer: PrettierPrinter) {
+        let config = printer
+            .with_color(ColorChoice::Never)
+            .with_width_fn(|_| 0)
+            .with_sparkle_ratio(1, 1)
+            .config;
+        for seed in 0..20 {
+            let mut printer = PrettierPrinter {
+                config: config.clone(),
+                ..PrettierPrinter::new_with_seed([seed; 32])
+            };
+            for debug_str in ["", "  x", "a\nb", "    日本\n  abc  "] {
+                let result = printer.print_display(&debug_str).to_string();
+                assert_eq!(result.lines().count(), debug_str.lines().count() + 2);
+            }
+        }
+    }
+
+    #[test]
+    fn with_breadcrumbs() {
+        #[derive(Debug)]
+        #[allow(dead_code)]
+        struct Inner {
+            timeout: u32,
+        }
+
+        #[derive(Debug)]
+        #[allow(dead_code)]
+        struct Outer {
+            inner: Inner,
+        }
+
+        let result = PrettierPrinter::new_with_seed([0; 32])
+            .with_color(ColorChoice::Never)
+            .with_breadcrumbs(true)
+            .print(&Outer {
+                inner: Inner { timeout: 30 },
+            })
+            .to_string();
+        let leaf = result
+            .lines()
+            .find(|line| line.contains("timeout: 30"))
+            .unwrap();
+        assert!(leaf.starts_with(" root.inner.timeout: "), "{}", result);
+    }
+
+    #[test]
+    fn breadcrumbs_prefix() {
+        let result = PrettierPrinter::new_with_seed([0; 32])
+            .with_color(ColorChoice::Always)
+            .with_breadcrumbs(true)
+            .with_rainbow_text(true)
+            .with_sparkle_ratio(0, 1)
+            .with_line_transform(|line| line.replace('0', "o"))
+            .print_with_width_hint(&vec![10000, 2], 8)
+            .to_string();
+        let lines: Vec<&str> = result.lines().collect();
+        // The prefix is dim, and is not colored, cut off, or transformed
+        let dim = |prefix: &str| format!("\u{1b}[2m{}\u{1b}[22m", prefix);
+        assert!(lines[1].starts_with(&format!(" {}", dim("root:    "))));
+        assert!(lines[2].starts_with(&format!(" {}", dim("root[0]: "))));
+        assert_eq!(color::strip_ansi(lines[2]).trim_end(), " root[0]:     1ooo");
+        assert!(color::strip_ansi(lines[3]).starts_with(" root[1]:     2"));
+    }
+
+    #[test]
+    fn inline_corners() {
+        let mut printer = PrettierPrinter::new_with_seed([0; 32])
+            .with_color(ColorChoice::Never)
+            .with_style(Style::InlineCorners);
+        assert_eq!(printer.print(&vec![1, 2]).to_string(), "🌈[1, 2]🌈");
+        assert_eq!(printer.print(&"a").to_string(), "🌈\"a\"🌈");
+        assert_eq!(printer.print_display(&"a\nb").to_string(), "🌈a b🌈");
+    }
+
+    #[cfg(feature = "rng-trace")]
+    #[test]
+    fn output_trace() {
+        let debug_str = format!("{:#?}", (0..20).map(|i| "x".repeat(i)).collect::<Vec<_>>());
+        for seed in 0..10 {
+            let (output, draws) =
+                PrettierPrintDisplayer::<()>::output_trace([seed; 32], &debug_str);
+            assert_eq!(
+                output,
+                PrettierPrintDisplayer::<()>::output([seed; 32], &debug_str)
+            );
+            assert!(draws
+                .iter()
+                .any(|draw| matches!(draw, RngDraw::Sparkle(true))));
+            assert!(draws.iter().any(|draw| matches!(draw, RngDraw::Star(_))));
+            assert!(draws
+                .iter()
+                .any(|draw| matches!(draw, RngDraw::Position(_))));
+            assert_eq!(
+                PrettierPrintDisplayer::<()>::output_replay(&draws, &debug_str),
+                output
+            );
+        }
+
+        // Every star is the second star of the palette
+        let (output, draws) = PrettierPrintDisplayer::<()>::output_trace([0; 32], &debug_str);
+        let edited: Vec<RngDraw> = draws
+            .iter()
+            .map(|&draw| match draw {
+                RngDraw::Star(_) => RngDraw::Star(1),
+                draw => draw,
+            })
+            .collect();
+        let replayed = PrettierPrintDisplayer::<()>::output_replay(&edited, &debug_str);
+        let star_count = |s: &str| s.chars().filter(|c| DEFAULT_STARS.contains(c)).count();
+        assert_eq!(star_count(&replayed), star_count(&output));
+        assert_eq!(
+            star_count(&replayed),
+            replayed.chars().filter(|&c| c == DEFAULT_STARS[1]).count()
+        );
+    }
+
+    #[cfg(feature = "rng-trace")]
+    #[test]
+    #[should_panic(expected = "trace has a star that is not in the palette")]
+    fn output_replay_star_out_of_range() {
+        let debug_str = format!("{:#?}", (0..20).map(|i| "x".repeat(i)).collect::<Vec<_>>());
+        let (_, draws) = PrettierPrintDisplayer::<()>::output_trace([0; 32], &debug_str);
+        let edited: Vec<RngDraw> = draws
+            .iter()
+            .map(|&draw| match draw {
+                RngDraw::Star(_) => RngDraw::Star(DEFAULT_STARS.len()),
+                draw => draw,
+            })
+            .collect();
+        PrettierPrintDisplayer::<()>::output_replay(&edited, &debug_str);
+    }
+
+    #[test]
+    fn wide_characters() {
+        let config = PrettierConfig {
+            color: ColorChoice::Never,
+            ..PrettierConfig::default()
+        };
+        for seed in 0..10 {
+            // Six columns, not nine bytes
+            let result = config.output([seed; 32], "日本語\nabc");
+            let lines: Vec<&str> = result.lines().collect();
+            assert_eq!(display_width(lines[0], 2), box_width(6) + 2);
+            assert!(lines[1].starts_with(" 日本語"));
+        }
+    }
+
+    #[test]
+    fn long_numbers() {
+        let config = PrettierConfig {
+            color: ColorChoice::Never,
+            ..PrettierConfig::default()
+        };
+        for debug_str in &[format!("{:#?}", i128::MIN), "9".repeat(50)] {
+            for seed in 0..10 {
+                let result = config.output([seed; 32], debug_str);
+                let lines: Vec<&str> = result.lines().collect();
+                let width = display_width(lines[0], 2);
+                assert_eq!(width, box_width(debug_str.len()) + 2);
+                assert_eq!(display_width(lines[2], 2), width);
+                assert!(display_width(lines[1], 2) <= width, "{}", result);
+            }
+        }
+    }
+
+    #[rstest]
+    #[case(PrettierPrinter::default())]
+    #[case(PrettierPrinter::default().with_rainbow_text(true).with_indent(2))]
+    #[case(PrettierPrinter::default().with_frame_rainbow(true).with_sparkle_ratio(1, 1))]
+    #[case(PrettierPrinter::default().with_star_location(StarLocation::Border))]
+    #[case(PrettierPrinter::default().with_density_per_column(0.5).with_emoji_width(1))]
+    #[case(PrettierPrinter::default().with_flip(Flip::Horizontal))]
+    #[case(PrettierPrinter::default().with_flip(Flip::Vertical).with_shadow(true))]
+    fn render_color_grid(#[case] printer: PrettierPrinter) {
+        let config = printer.with_color(ColorChoice::Always).config;
+        // ☀ is also in the palette
+        let debug_str = "[\n    \"☀\",\n    1,\n]";
+        for seed in 0..10 {
+            let grid = config.render_color_grid([seed; 32], debug_str);
+            let (width, height) = config.measure([seed; 32], debug_str);
+            assert_eq!(grid.len(), height);
+            assert!(grid.iter().all(|row| row.len() == width));
+
+            let output = config.output([seed; 32], debug_str);
+            let colored: Vec<(char, Color)> = color::parse_colored(&output)
+                .into_iter()
+                .filter(|&(c, _)| c != ' ' && c != '\n')
+                .collect();
+            let cells: Vec<(char, Color)> = grid
+                .iter()
+                .flatten()
+                .copied()
+                .filter(|&(c, _)| c != ' ')
+                .collect();
+            assert_eq!(cells, colored);
+
+            let sun = grid
+                .iter()
+                .flat_map(|row| row.windows(2))
+                .find(|cells| cells[0].0 == '"' && cells[1].0 == '☀')
+                .map(|cells| cells[1].1);
+            if config.rainbow_text {
+                assert_ne!(sun, Some(Color::Reset));
+            } else if config.flip == Flip::None {
+                assert_eq!(sun, Some(Color::Reset));
+            }
+        }
+    }
+
+    #[rstest]
+    #[case("\t┐", [2; 32])]
+    #[case("a\u{301}", [2; 32])]
+    #[case("  x\u{301}y", [0; 32])]
+    #[case("\ta\u{301}\n  b\tc", [1; 32])]
+    fn render_color_grid_zero_width(#[case] debug_str: &str, #[case] seed: Seed) {
+        let config = PrettierConfig {
+            color: ColorChoice::Always,
+            sparkle_ratio: (1, 1),
+            ..PrettierConfig::default()
+        };
+        let grid = config.render_color_grid(seed, debug_str);
+        let (width, height) = config.measure(seed, debug_str);
+        assert_eq!(grid.len(), height);
+        assert!(grid.iter().all(|row| row.len() == width));
+
+        let output = config.output(seed, debug_str);
+        let colored: Vec<(char, Color)> = color::parse_colored(&output)
+            .into_iter()
+            .filter(|&(c, _)| c != ' ' && c != '\n' && config.char_width(c) > 0)
+            .collect();
+        let cells: Vec<(char, Color)> = grid
+            .iter()
+            .flatten()
+            .copied()
+            .filter(|&(c, _)| c != ' ')
+            .collect();
+        assert_eq!(cells, colored);
+        assert!(grid
+            .iter()
+            .flatten()
+            .all(|&(c, _)| c != '\t' && c != '\u{301}'));
+    }
+
+    #[rstest]
+    #[case(PrettierPrinter::default())]
+    #[case(PrettierPrinter::default().with_frame_rainbow(true).with_indent(2))]
+    #[case(PrettierPrinter::default().with_star_location(StarLocation::Border).with_fill('.'))]
+    fn apply_plan(#[case] printer: PrettierPrinter) {
+        let star_columns = |s: &str| -> Vec<Option<usize>> {
+            s.lines()
+                .map(|line| {
+                    let i = line.find(|c| DEFAULT_STARS.contains(&c))?;
+                    Some(display_width(&line[..i], 2))
+                })
+                .collect()
+        };
+
+        let config = printer.with_color(ColorChoice::Never).config;
+        for seed in 0..10 {
+            let mut printer =
+                PrettierPrinter::new_with_seed([seed; 32]).with_config(config.clone());
+            let plan = printer.plan(5, 3);
+            let first = printer.apply_plan(&plan, "a\nbb\nc");
+            let second = printer.apply_plan(&plan, "dd\ne\nf");
+            assert_eq!(star_columns(&first), star_columns(&second));
+            assert!(first.contains("bb") && second.contains("dd"));
+            assert_eq!(first.lines().count(), 5);
+        }
+    }
+
+    #[test]
+    fn apply_plan_matches_output() {
+        // Without stars, the plan adds nothing that the output does not have
+        let printer = PrettierPrinter::new_with_seed([0; 32])
+            .with_sparkle_ratio(0, 1)
+            .with_color(ColorChoice::Always)
+            .with_frame_rainbow(true)
+            .with_rainbow_text(true)
+            .with_fill('.')
+            .with_indent(2)
+            .with_shadow(true);
+        let debug_str = "abc\n  de\nf";
+        for seed in 0..10 {
+            let plan = printer.config.plan([seed; 32], 4, 3);
+            assert_eq!(
+                printer.apply_plan(&plan, debug_str),
+                printer.config.output([seed; 32], debug_str)
+            );
+        }
+    }
+
+    #[test]
+    fn shadow() {
+        let config = PrettierConfig {
+            shadow: true,
+            indent: 2,
+            ..PrettierConfig::default()
+        };
+        let debug_str = "abc\nde\nf";
+        for seed in 0..10 {
+            let result = config.output([seed; 32], debug_str);
+            let unshadowed = PrettierConfig {
+                indent: 2,
+                ..PrettierConfig::default()
+            }
+            .output([seed; 32], debug_str);
+            let lines: Vec<&str> = result.lines().collect();
+            let box_width = display_width(unshadowed.lines().next().unwrap(), 2);
+            let box_height = unshadowed.lines().count();
+            assert_eq!(lines.len(), box_height + 1);
+
+            // Columns of the shadow on each row
+            let shadow_columns: Vec<Vec<usize>> = lines
+                .iter()
+                .map(|line| {
+                    let mut column = 0;
+                    let mut columns = Vec::new();
+                    for c in line.chars() {
+                        if c == SHADOW {
+                            columns.push(column);
+                        }
+                        column += width::char_width(c, 2);
+                    }
+                    columns
+                })
+                .collect();
+            assert!(shadow_columns[0].is_empty());
+            for columns in &shadow_columns[1..box_height] {
+                assert_eq!(columns, &[box_width]);
+            }
+            let bottom: Vec<usize> = (3..=box_width).collect();
+            assert_eq!(shadow_columns[box_height], bottom);
+        }
+    }
+
+    #[test]
+    fn shadow_wide_star() {
+        // The stars are two columns wide, and the border is one
+        let config = PrettierPrinter::default()
+            .with_emoji_width(1)
+            .with_palette(&['日'], &[1])
+            .with_sparkle_ratio(1, 1)
+            .with_shadow(true)
+            .config;
+        let debug_str = "abcdefghij\nabc";
+        for seed in 0..20 {
+            let result = config.output([seed; 32], debug_str);
+            let lines: Vec<&str> = result.lines().collect();
+            let box_width = config.display_width(lines[0]);
+            assert!(lines[1].contains('日'), "{}", result);
+            for line in &lines[1..lines.len() - 1] {
+                let shadow = line.find(SHADOW).unwrap();
+                assert_eq!(
+                    config.display_width(&line[..shadow]),
+                    box_width,
+                    "{}",
+                    result
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn print_timed() {
+        let start = Instant::now();
+        let mut calls = 0;
+        let clock = || {
+            calls += 1;
+            start + std::time::Duration::from_micros(1500) * (calls - 1)
+        };
+        let result = PrettierPrinter::default()
+            .print_timed_with_clock(&vec![1, 2], clock)
+            .to_string();
+        let lines: Vec<&str> = result.lines().collect();
+        assert!(lines[lines.len() - 2].starts_with(" formatted in 1.5ms"));
+        assert!(lines[1].starts_with(" ["));
+
+        let result = PrettierPrinter::default().print_timed(&1).to_string();
+        let footer = result.lines().nth(2).unwrap();
+        assert!(footer.starts_with(" formatted in "));
+        assert!(footer.contains("s"));
+    }
+
+    #[rstest]
+    #[case(Flip::None, "🌈  🌈\n ab\n c\u{301}\n🌈  🌈\n")]
+    #[case(Flip::Vertical, "🌈  🌈\n c\u{301}\n ab\n🌈  🌈\n")]
+    #[case(Flip::Horizontal, "🌈  🌈\n   ba\n    c\u{301}\n🌈  🌈\n")]
+    fn with_flip(#[case] flip: Flip, #[case] expected: &str) {
+        let config = PrettierConfig {
+            flip,
+            sparkle_ratio: (0, 1),
+            rainbow_text: true,
+            ..PrettierConfig::default()
+        };
+        let mut result = String::new();
+        config
+            .render([0; 32], "ab\nc\u{301}", &mut result, true)
+            .unwrap();
+        assert_eq!(color::strip_ansi(&result), expected);
+
+        // Each character keeps its color
+        let colors = |s: &str| -> Vec<(char, Color)> {
+            let mut colors: Vec<(char, Color)> = color::parse_colored(s)
+                .into_iter()
+                .filter(|&(c, _)| c.is_ascii_alphabetic())
+                .collect();
+            colors.sort_by_key(|&(c, _)| c);
+            colors
+        };
+        let mut unflipped = String::new();
+        PrettierConfig {
+            flip: Flip::None,
+            ..config
+        }
+        .render([0; 32], "ab\nc\u{301}", &mut unflipped, true)
+        .unwrap();
+        assert_eq!(colors(&result), colors(&unflipped));
+        assert!(colors(&result)
+            .iter()
+            .all(|&(_, color)| color != Color::Reset));
+    }
+
+    #[test]
+    fn decorrelated_lines() {
+        let config = PrettierConfig {
+            decorrelated_lines: true,
+            sparkle_ratio: (1, 1),
+            ..PrettierConfig::default()
+        };
+        let debug_str = format!("{}\n", "a".repeat(100)).repeat(6);
+        let result = config.output([1; 32], &debug_str);
+        assert_eq!(result, config.output([1; 32], &debug_str));
+        assert_ne!(
+            result,
+            PrettierConfig::default().output([1; 32], &debug_str)
+        );
+
+        let lines: Vec<&str> = result.lines().collect();
+        for pair in lines[1..lines.len() - 1].windows(2) {
+            assert_ne!(pair[0], pair[1]);
+        }
+    }
+
+    #[test]
+    fn hex_dump() {
+        let bytes: Vec<u8> = (b'a'..b'a' + 18).chain([0, 0xff]).collect();
+        assert_eq!(
+            super::hex_dump(&bytes, 16),
+            "00000000  61 62 63 64 65 66 67 68 69 6a 6b 6c 6d 6e 6f 70  |abcdefghijklmnop|\n\
+             00000010  71 72 00 ff                                      |qr..|"
+        );
+        assert_eq!(
+            super::hex_dump(&bytes[..5], 4),
+            "00000000  61 62 63 64  |abcd|\n00000004  65           |e|"
+        );
+        assert_eq!(super::hex_dump(&[], 16), "");
+
+        let result = PrettierPrinter::new_with_seed(Seed::default())
+            .print_bytes(&bytes)
+            .to_string();
+        let lines: Vec<&str> = result.lines().collect();
+        assert_eq!(lines.len(), 4);
+        assert!(lines[1].starts_with(" 00000000  61 62"));
+        assert!(lines[2].starts_with(" 00000010  71 72"));
+    }
+
+    #[test]
+    fn color_choice() {
+        let path = std::env::temp_dir().join(format!("prettier-print-{}", std::process::id()));
+        let render = |color| {
+            let mut file = std::fs::File::create(&path).unwrap();
+            PrettierPrinter::new_with_seed(Seed::default())
+                .with_rainbow_text(true)
+                .with_color(color)
+                .print(&vec![1, 2])
+                .output_to(&mut file)
+                .unwrap();
+            std::fs::read_to_string(&path).unwrap()
+        };
+
+        let always = render(ColorChoice::Always);
+        assert!(always.contains('\x1b'));
+        let never = render(ColorChoice::Never);
+        assert!(!never.contains('\x1b'));
+        assert_eq!(color::strip_ansi(&always), never);
+        // A file is not a terminal
+        assert_eq!(render(ColorChoice::Auto), never);
+        std::fs::remove_file(&path).unwrap();
+
+        // Whether these go to a terminal is not known
+        let mut printer = PrettierPrinter::new_with_seed(Seed::default())
+            .with_rainbow_text(true)
+            .with_color(ColorChoice::Auto);
+        assert_eq!(printer.print(&vec![1, 2]).to_string(), never);
+        let mut written: Vec<u8> = Vec::new();
+        printer
+            .decorate_reader("[\n    1,\n]".as_bytes(), &mut written, 4)
+            .unwrap();
+        assert!(!written.contains(&b'\x1b'));
+        let plan = printer.plan(4, 3);
+        assert!(!printer.apply_plan(&plan, "[\n    1,\n]").contains('\x1b'));
+    }
+
+    #[test]
+    fn density_per_column() {
+        let config = PrettierConfig {
+            density_per_column: Some(0.2),
+            ..PrettierConfig::default()
+        };
+        let count_stars = |line: &str| line.chars().filter(|c| DEFAULT_STARS.contains(c)).count();
+        for seed in 0..10 {
+            let result = config.output([seed; 32], &format!("a\n{}", "a".repeat(40)));
+            let lines: Vec<&str> = result.lines().collect();
+            assert!(count_stars(lines[1]) > count_stars(lines[2]));
+            for line in &lines[1..3] {
+                assert!(display_width(line, 2) <= display_width(lines[0], 2) + 1);
+            }
+        }
+    }
+
+    #[test]
+    fn star_density() {
+        let config = PrettierConfig {
+            star_density: Some(5.0),
+            ..PrettierConfig::default()
+        };
+        let count_stars =
+            |result: &str| result.chars().filter(|c| DEFAULT_STARS.contains(c)).count();
+        let debug_str = |line_count: usize| "a\n".repeat(line_count) + &"a".repeat(40);
+        for seed in 0..10 {
+            let small = count_stars(&config.output([seed; 32], &debug_str(10)));
+            let large = count_stars(&config.output([seed; 32], &debug_str(21)));
+            assert!(small > 0);
+            assert!(
+                (small * 3 / 2..=small * 5 / 2).contains(&large),
+                "{} {}",
+                small,
+                large
+            );
+        }
+
+        let none = PrettierConfig {
+            star_density: Some(0.0),
+            ..PrettierConfig::default()
+        };
+        assert_eq!(count_stars(&none.output([0; 32], &debug_str(10))), 0);
+    }
+
+    #[test]
+    fn column_split() {
+        let debug_str: String = (0..20).map(|i| format!("line {:02}\n", i)).collect();
+        let split = PrettierConfig {
+            column_split: 2,
+            ..PrettierConfig::default()
+        };
+        let result = split.output([0; 32], &debug_str);
+        let box_width = |s: &str| display_width(s.lines().next().unwrap(), 2);
+        assert!(box_width(&result) <= 80);
+        assert_eq!(result.lines().count(), 10 + 2);
+        for i in 0..20 {
+            assert!(result.contains(&format!("line {:02}", i)));
+        }
+        for line in result.lines() {
+            assert!(display_width(line, 2) <= box_width(&result) + 1);
+        }
+    }
+
+    #[rstest]
+    #[case(StarLocation::Interior, None)]
+    #[case(StarLocation::Border, None)]
+    #[case(StarLocation::Interior, Some(0.5))]
+    fn max_total_stars(#[case] star_location: StarLocation, #[case] density: Option<f64>) {
+        let debug_str: String = (0..50)
+            .map(|i| {
+                format!(
+                    "    line {:02} of a box that is wide enough for many stars\n",
+                    i
+                )
+            })
+            .collect();
+        let count_stars = |s: &str| s.chars().filter(|c| DEFAULT_STARS.contains(c)).count();
+        for seed in 0..10 {
+            let config = PrettierConfig {
+                star_location,
+                density_per_column: density,
+                ..PrettierConfig::default()
+            };
+            assert!(count_stars(&config.output([seed; 32], &debug_str)) > 2);
+
+            let config = PrettierConfig {
+                max_total_stars: Some(2),
+                ..config
+            };
+            let result = config.output([seed; 32], &debug_str);
+            assert!(count_stars(&result) <= 2);
+            for i in 0..50 {
+                assert!(result.contains(&format!("line {:02} ", i)));
+            }
+        }
+    }
+
+    #[test]
+    fn decorate_reader() {
+        let debug_str = format!("{:#?}", vec![1, 2, 3]);
+        let mut result = Vec::new();
+        PrettierPrinter::new_with_seed([0; 32])
+            .decorate_reader(debug_str.as_bytes(), &mut result, 10)
+            .unwrap();
+        let result = String::from_utf8(result).unwrap();
+        let lines: Vec<&str> = result.lines().collect();
+        assert_eq!(lines.len(), debug_str.lines().count() + 2);
+        assert_eq!(display_width(lines[0], 2), box_width(10) + 2);
+        for (line, expected) in lines[1..].iter().zip(debug_str.lines()) {
+            assert!(line.contains(expected.trim_start()));
+        }
+
+        // Two passes give the same box as printing the whole string at once
+        let mut result = Vec::new();
+        PrettierPrinter::new_with_seed([0; 32])
+            .decorate_reader_two_pass(io::Cursor::new(&debug_str), &mut result)
+            .unwrap();
+        let expected = PrettierPrinter::new_with_seed([0; 32])
+            .print(&vec![1, 2, 3])
+            .to_string();
+        assert_eq!(String::from_utf8(result).unwrap(), expected);
+    }
+
+    #[rstest]
+    #[case(None)]
+    #[case(Some(0.2))]
+    fn fill(#[case] density: Option<f64>) {
+        let config = PrettierConfig {
+            fill: '.',
+            density_per_column: density,
+            star_location: StarLocation::Interior,
+            ..PrettierConfig::default()
+        };
+        let debug_str = "a\nbbbbbbbbbbbbbbbbbbbb\ncontent...\n";
+        for seed in 0..10 {
+            let result = config.output([seed; 32], debug_str);
+            let lines: Vec<&str> = result.lines().collect();
+            assert!(lines[0].contains('.'));
+            for line in &lines[1..lines.len() - 1] {
+                assert!(!line.ends_with('.') || line.ends_with("content..."));
+            }
+            assert!(lines[3].contains("content..."));
+        }
+    }
+
+    #[test]
+    fn queue_decorated() {
+        let debug_str = format!("{:#?}", vec![1, 2]);
+        let mut result = Vec::new();
+        PrettierPrintDisplayer::<()>::queue_decorated(&mut result, [1; 32], &debug_str, (3, 5))
+            .unwrap();
+
+        let mut expected = Vec::new();
+        let output = PrettierPrintDisplayer::<()>::output([1; 32], &debug_str);
+        for (row, line) in output.lines().enumerate() {
+            queue!(expected, MoveTo(3, 5 + row as u16), Print(line)).unwrap();
+        }
+        assert_eq!(result, expected);
+
+        let mut first_command = Vec::new();
+        queue!(first_command, MoveTo(3, 5)).unwrap();
+        assert!(result.starts_with(&first_command));
+
+        // Only the rows that fit are drawn
+        let mut result = Vec::new();
+        let origin = (0, u16::MAX - 2);
+        PrettierPrintDisplayer::<()>::queue_decorated(&mut result, [1; 32], &debug_str, origin)
+            .unwrap();
+        let mut expected = Vec::new();
+        for (row, line) in output.lines().take(2).enumerate() {
+            queue!(expected, MoveTo(0, origin.1 + row as u16), Print(line)).unwrap();
+        }
+        assert_eq!(result, expected);
+    }
+
+    #[rstest]
+    #[case(&[], "")]
+    #[case(&[vec![1, 20], vec![300, 4]], "  1  20\n300   4")]
+    #[case(&[vec![1, 2, 3], vec![4]], "1  2  3\n4")]
+    #[case(&[vec![1000, 2], vec![3, 4]], "1000  2\n   3  4")]
+    #[case(&[vec![], vec![10]], "\n10")]
+    fn grid_string(#[case] rows: &[Vec<i32>], #[case] expected: &str) {
+        assert_eq!(super::grid_string(rows), expected);
+    }
+
+    #[test]
+    fn map_string() {
+        let map: HashMap<&str, i32> = [("a", 1), ("ccc", 3), ("bb", 2)].into();
+        assert_eq!(
+            super::map_string(&map, true),
+            "\"a\"  : 1\n\"bb\" : 2\n\"ccc\": 3"
+        );
+        assert_eq!(super::map_string(Vec::<(i32, i32)>::new(), true), "");
+    }
+
+    #[test]
+    fn print_map() {
+        let map: HashMap<&str, i32> = [("a", 1), ("ccc", 3), ("bb", 2)].into();
+        let result = PrettierPrinter::default().print_map(&map, true).to_string();
+        let lines: Vec<&str> = result.lines().collect();
+        assert_eq!(lines.len(), 3 + 2);
+        let colons: Vec<usize> = lines[1..4]
+            .iter()
+            .map(|line| line.trim_start_matches(' ').find(':').unwrap())
+            .collect();
+        assert_eq!(colons, [5, 5, 5]);
+    }
+
+    #[test]
+    fn print_kv() {
+        assert_eq!(
+            kv_string(
+                &[("id", &7), ("user", &"alice"), ("elapsed_ms", &1.5)],
+                |s| display_width(s, 2)
+            ),
+            "        id = 7\n      user = \"alice\"\nelapsed_ms = 1.5"
+        );
+        assert_eq!(
+            kv_string(&[("名前", &1), ("id", &2)], |s| display_width(s, 2)),
+            "名前 = 1\n  id = 2"
+        );
+        assert_eq!(kv_string(&[], |s| display_width(s, 2)), "");
+
+        let result = PrettierPrinter::new_with_seed(Seed::default())
+            .with_color(ColorChoice::Never)
+            .print_kv(&[("id", &7), ("user", &"alice"), ("名前", &1.5)])
+            .to_string();
+        let lines: Vec<&str> = result.lines().collect();
+        assert_eq!(lines.len(), 3 + 2);
+        let equals: Vec<usize> = lines[1..4]
+            .iter()
+            .map(|line| display_width(&line[..line.find(" = ").unwrap()], 2))
+            .collect();
+        assert_eq!(equals, [equals[0]; 3]);
+    }
+
+    #[test]
+    fn print_grid() {
+        let result = PrettierPrinter::new_with_seed(Seed::default())
+            .print_grid(&[vec![1, 20], vec![300, 4]])
+            .to_string();
+        let expected = "🌈       🌈
+   1  20⭐
+ 300   4 🌟
+🌈       🌈
+";
+        assert_eq!(result, expected);
+
+        let result = PrettierPrinter::new_with_seed(Seed::default())
+            .print_grid::<i32>(&[])
+            .to_string();
+        assert_eq!(result, "🌈🌈\n🌈🌈\n");
+    }
+}
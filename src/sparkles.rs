@@ -1,5 +1,7 @@
+use crate::color;
 use crate::game_of_life::{Board, Cell};
-use crate::prettier_printer::{PrettierPrinter, Seed};
+use crate::prettier_printer::{PrettierPrinter, Seed, WidthFn};
+use crate::width::display_width;
 use crossterm::cursor;
 use crossterm::cursor::{MoveTo, MoveToNextLine};
 use crossterm::event::poll;
@@ -7,13 +9,12 @@ use crossterm::style::{Color, Colors, Print, SetBackgroundColor, SetColors};
 use crossterm::terminal::{disable_raw_mode, enable_raw_mode, Clear, ClearType};
 use crossterm::{queue, terminal};
 use rand::rngs::SmallRng;
-use rand::SeedableRng;
+use rand::{Rng, SeedableRng};
 use std::fmt::Debug;
 use std::io::{StdoutLock, Write};
-use std::iter::once;
 use std::str::Chars;
 use std::thread::sleep;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 /// Prints the debug string, and runs game of life on top of the printed string. The output covers
 /// the full terminal screen.
@@ -22,6 +23,11 @@ use std::time::Duration;
 pub struct Sparkles<'stream> {
     rng: SmallRng,
     stdout: StdoutLock<'stream>,
+    marquee: bool,
+    revive_on_extinction: bool,
+    frame_shimmer: bool,
+    background_texture: Option<(f64, Color)>,
+    width_fn: Option<WidthFn>,
 }
 
 impl<'stream> Sparkles<'stream> {
@@ -30,6 +36,11 @@ impl<'stream> Sparkles<'stream> {
         Self {
             rng: SmallRng::from_entropy(),
             stdout,
+            marquee: false,
+            revive_on_extinction: false,
+            frame_shimmer: false,
+            background_texture: None,
+            width_fn: None,
         }
     }
 
@@ -37,6 +48,64 @@ impl<'stream> Sparkles<'stream> {
         Self {
             rng: SmallRng::from_seed(seed),
             stdout,
+            marquee: false,
+            revive_on_extinction: false,
+            frame_shimmer: false,
+            background_texture: None,
+            width_fn: None,
+        }
+    }
+
+    /// Draws stars that travel around the debug string, one cell per frame.
+    pub fn with_marquee(mut self, marquee: bool) -> Self {
+        self.marquee = marquee;
+        self
+    }
+
+    /// Draws rainbow edges on the left and right of the debug string. The colors cycle with each
+    /// frame and row.
+    pub fn with_frame_shimmer(mut self, frame_shimmer: bool) -> Self {
+        self.frame_shimmer = frame_shimmer;
+        self
+    }
+
+    /// Draws faint stars in `color` on a fraction `density` of the blank dead cells, so that the
+    /// background is not flat. The stars stay in the same place for the whole run.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `density` is not between 0 and 1.
+    pub fn with_background_texture(mut self, density: f64, color: Color) -> Self {
+        assert!(
+            (0.0..=1.0).contains(&density),
+            "density must be between 0 and 1; got {}",
+            density
+        );
+        self.background_texture = Some((density, color));
+        self
+    }
+
+    /// Fills the board with random cells again when every cell has died. Defaults to false.
+    pub fn with_revive_on_extinction(mut self, revive_on_extinction: bool) -> Self {
+        self.revive_on_extinction = revive_on_extinction;
+        self
+    }
+
+    /// Measures the number of columns each character takes up with `width_fn` instead of the
+    /// Unicode width to center the debug string, like [`PrettierPrinter::with_width_fn()`].
+    pub fn with_width_fn<F>(mut self, width_fn: F) -> Self
+    where
+        F: Fn(char) -> usize + Send + Sync + 'static,
+    {
+        self.width_fn = Some(WidthFn::new(width_fn));
+        self
+    }
+
+    /// Number of columns `s` takes up. See [`Sparkles::with_width_fn()`].
+    fn display_width(&self, s: &str) -> usize {
+        match &self.width_fn {
+            Some(width_fn) => width_fn.str_width(s),
+            None => display_width(s, 2),
         }
     }
 
@@ -45,66 +114,364 @@ impl<'stream> Sparkles<'stream> {
     where
         T: Debug,
     {
-        enable_raw_mode().unwrap();
-        queue!(
-            self.stdout,
-            Clear(ClearType::All),
-            MoveTo(0, 0),
-            SetColors(Colors::new(Color::Reset, Color::Reset)),
-            cursor::Hide,
-        )?;
+        self.run_while(what, || Ok(true))
+    }
+
+    /// Runs the output screen until `duration` has passed or any key is pressed.
+    pub fn run_for<T>(&mut self, what: &T, duration: Duration) -> std::io::Result<()>
+    where
+        T: Debug,
+    {
+        self.run_while(what, until_expired(duration, Instant::now))
+    }
+
+    /// Returns the first `frames` frames of the animation as plain text, without writing to the
+    /// terminal. Live cells are `#` and dead cells are spaces, with the debug string drawn on top.
+    /// Each row ends with a newline. The other options do not apply.
+    pub fn text_frames<T>(
+        &mut self,
+        what: &T,
+        frames: usize,
+        terminal_size: (u16, u16),
+    ) -> Vec<String>
+    where
+        T: Debug,
+    {
+        let debug_str = format!("{:#?}", what);
+        let mut board = Board::new(PrettierPrinter::gen_seed(&mut self.rng), terminal_size);
+        let mut result = Vec::with_capacity(frames);
+        for _ in 0..frames {
+            let display_width = |s: &str| self.display_width(s);
+            result.push(text_frame(&board, &debug_str, terminal_size, display_width));
+
+            board.tick();
+            if should_revive(self.revive_on_extinction, board.live_count()) {
+                board.randomize(PrettierPrinter::gen_seed(&mut self.rng));
+            }
+        }
+        result
+    }
+
+    /// Renders frames while `keep_running` returns true and no key has been pressed.
+    fn run_while<T, F>(&mut self, what: &T, keep_running: F) -> std::io::Result<()>
+    where
+        T: Debug,
+        F: FnMut() -> std::io::Result<bool>,
+    {
+        enter_screen(&mut self.stdout)?;
 
         let terminal_size = terminal::size().unwrap();
 
         let debug_str = format!("{:#?}", what);
 
         let mut board = Board::new(PrettierPrinter::gen_seed(&mut self.rng), terminal_size);
-        while !poll(Duration::from_secs(0))? {
-            queue!(self.stdout, MoveTo(0, 0))?;
-
-            let mut debug_str = CenteredDebugString::new(
-                &debug_str,
-                (terminal_size.0 as usize, terminal_size.1 as usize),
-            );
-
-            for (i, cell) in board.cell_array().iter().enumerate() {
-                let color = match cell {
-                    Cell::Dead => Color::Reset,
-                    Cell::Live => Color::White,
-                };
+        let texture = match self.background_texture {
+            Some((density, _)) => background_texture(
+                PrettierPrinter::gen_seed(&mut self.rng),
+                board.cell_array().len(),
+                density,
+            ),
+            None => Vec::new(),
+        };
+        let key_pressed = || poll(Duration::from_secs(0));
+        frame_loop(keep_running, key_pressed, |frame| {
+            self.render_frame(&board, &debug_str, &texture, terminal_size, frame)?;
+
+            board.tick();
+            if should_revive(self.revive_on_extinction, board.live_count()) {
+                board.randomize(PrettierPrinter::gen_seed(&mut self.rng));
+            }
+
+            sleep(FRAME_DURATION);
+            Ok(())
+        })?;
+
+        leave_screen(&mut self.stdout)
+    }
+
+    fn render_frame(
+        &mut self,
+        board: &Board,
+        debug_str: &str,
+        texture: &[bool],
+        terminal_size: (u16, u16),
+        frame: usize,
+    ) -> std::io::Result<()> {
+        let terminal_size = (terminal_size.0 as usize, terminal_size.1 as usize);
+        queue!(self.stdout, MoveTo(0, 0))?;
+
+        let perimeter = Perimeter::new(debug_str, terminal_size, |s| self.display_width(s));
+        let mut debug_str = CenteredDebugString::new_with_width_fn(debug_str, terminal_size, |s| {
+            self.display_width(s)
+        });
+
+        for (i, cell) in board.cell_array().iter().enumerate() {
+            let color = match cell {
+                Cell::Dead => Color::Reset,
+                Cell::Live => Color::White,
+            };
+            let mut c = debug_str.next().unwrap();
+            let mut foreground = Color::Reset;
+            if let Some((_, texture_color)) = self.background_texture {
+                if *cell == Cell::Dead && c == ' ' && texture[i] {
+                    c = TEXTURE_STAR;
+                    foreground = texture_color;
+                }
+            }
+            let position = (i % terminal_size.0, i / terminal_size.0);
+            if self.frame_shimmer && perimeter.is_side(position) {
+                c = FRAME_EDGE;
+                foreground = shimmer_color(frame, position.1);
+            }
+            if self.marquee && perimeter.is_marquee_star(position, frame) {
+                c = MARQUEE_STAR;
+            }
+            queue!(
+                self.stdout,
+                SetColors(Colors::new(foreground, color)),
+                Print(c)
+            )?;
+
+            // Line break
+            if i % terminal_size.0 == terminal_size.0 - 1 {
                 queue!(
                     self.stdout,
-                    SetBackgroundColor(color),
-                    Print(debug_str.next().unwrap())
+                    SetBackgroundColor(Color::Reset),
+                    MoveToNextLine(1),
                 )?;
-
-                // Line break
-                if i % terminal_size.0 as usize == terminal_size.0 as usize - 1 {
-                    queue!(
-                        self.stdout,
-                        SetBackgroundColor(Color::Reset),
-                        MoveToNextLine(1),
-                    )?;
-                }
-                self.stdout.flush()?;
             }
+            self.stdout.flush()?;
+        }
+        Ok(())
+    }
+}
 
-            board.tick();
+/// A frame of [`Sparkles::text_frames()`].
+fn text_frame<F>(
+    board: &Board,
+    debug_str: &str,
+    terminal_size: (u16, u16),
+    display_width: F,
+) -> String
+where
+    F: Fn(&str) -> usize,
+{
+    let terminal_size = (terminal_size.0 as usize, terminal_size.1 as usize);
+    let mut debug_str =
+        CenteredDebugString::new_with_width_fn(debug_str, terminal_size, display_width);
+    let mut result = String::with_capacity((terminal_size.0 + 1) * terminal_size.1);
+    for (x, _, cell) in board {
+        let c = debug_str.next().unwrap();
+        result.push(match (c, cell) {
+            (' ', Cell::Live) => TEXT_LIVE_CELL,
+            _ => c,
+        });
+        if x == terminal_size.0 - 1 {
+            result.push('\n');
+        }
+    }
+    result
+}
+
+fn should_revive(revive_on_extinction: bool, live_count: usize) -> bool {
+    revive_on_extinction && live_count == 0
+}
+
+const FRAME_DURATION: Duration = Duration::from_millis(50);
+
+/// Calls `step` with the frame number while `keep_running` returns true and `stop_requested`
+/// returns false. Returns the number of frames.
+fn frame_loop<K, S, F>(
+    mut keep_running: K,
+    mut stop_requested: S,
+    mut step: F,
+) -> std::io::Result<usize>
+where
+    K: FnMut() -> std::io::Result<bool>,
+    S: FnMut() -> std::io::Result<bool>,
+    F: FnMut(usize) -> std::io::Result<()>,
+{
+    let mut frame = 0;
+    while keep_running()? && !stop_requested()? {
+        step(frame)?;
+        frame += 1;
+    }
+    Ok(frame)
+}
+
+/// Keeps [`Sparkles::run_for()`] running until `duration` has passed according to `clock`.
+fn until_expired<C>(duration: Duration, clock: C) -> impl FnMut() -> std::io::Result<bool>
+where
+    C: FnMut() -> Instant,
+{
+    let mut timer = Timer::new(duration, clock);
+    move || Ok(!timer.expired())
+}
+
+/// Tells when `duration` has passed since the timer was created, according to `clock`.
+struct Timer<C> {
+    clock: C,
+    start: Instant,
+    duration: Duration,
+}
+
+impl<C> Timer<C>
+where
+    C: FnMut() -> Instant,
+{
+    fn new(duration: Duration, mut clock: C) -> Self {
+        Self {
+            start: clock(),
+            clock,
+            duration,
+        }
+    }
+
+    fn expired(&mut self) -> bool {
+        (self.clock)().saturating_duration_since(self.start) >= self.duration
+    }
+}
+
+/// Live cell in [`Sparkles::text_frames()`].
+const TEXT_LIVE_CELL: char = '#';
+
+const MARQUEE_STAR: char = '*';
+
+/// Distance between stars of the marquee.
+const MARQUEE_SPACING: usize = 4;
+
+const FRAME_EDGE: char = '│';
+
+const TEXTURE_STAR: char = '·';
+
+/// Decides which of `len` cells show a star of the background texture. Each cell shows one with
+/// probability `density`.
+fn background_texture(seed: Seed, len: usize, density: f64) -> Vec<bool> {
+    let mut rng = SmallRng::from_seed(seed);
+    (0..len).map(|_| rng.gen_bool(density)).collect()
+}
+
+/// Color of the edges from [`Sparkles::with_frame_shimmer()`] on `row` in `frame`.
+fn shimmer_color(frame: usize, row: usize) -> Color {
+    color::rainbow(frame + row)
+}
+
+/// The rectangle of cells surrounding the centered debug string.
+#[derive(Debug, Copy, Clone, PartialEq)]
+struct Perimeter {
+    left: usize,
+    top: usize,
+    right: usize,
+    bottom: usize,
+}
+
+impl Perimeter {
+    fn new<F>(s: &str, terminal_size: (usize, usize), display_width: F) -> Self
+    where
+        F: Fn(&str) -> usize,
+    {
+        let line_count = s.chars().filter(|&c| c == '\n').count() + 1;
+        let longest_line = CenteredDebugString::longest_line(s, display_width);
+        let top_margin = CenteredDebugString::margin_length(terminal_size.1, line_count);
+        let left_margin = CenteredDebugString::margin_length(terminal_size.0, longest_line);
+        Self {
+            left: left_margin.saturating_sub(1),
+            top: top_margin.saturating_sub(1),
+            right: (left_margin + longest_line).min(terminal_size.0.saturating_sub(1)),
+            bottom: (top_margin + line_count).min(terminal_size.1.saturating_sub(1)),
+        }
+    }
 
-            sleep(Duration::from_millis(50));
+    /// Number of cells on the perimeter.
+    fn len(&self) -> usize {
+        let width = self.right - self.left;
+        let height = self.bottom - self.top;
+        if width == 0 || height == 0 {
+            width + height + 1
+        } else {
+            (width + height) * 2
+        }
+    }
+
+    /// Returns the position of a cell on the perimeter, going clockwise from the top-left corner.
+    #[allow(dead_code)] // Used in test
+    fn position(&self, index: usize) -> (usize, usize) {
+        let width = self.right - self.left;
+        let height = self.bottom - self.top;
+        let index = index % self.len();
+        if index < width {
+            (self.left + index, self.top)
+        } else if index < width + height {
+            (self.right, self.top + index - width)
+        } else if index < width * 2 + height {
+            (self.right - (index - width - height), self.bottom)
+        } else {
+            (self.left, self.bottom - (index - width * 2 - height))
+        }
+    }
+
+    /// Inverse of `position()`. Returns `None` if `position` is not on the perimeter.
+    fn index(&self, (x, y): (usize, usize)) -> Option<usize> {
+        let width = self.right - self.left;
+        let height = self.bottom - self.top;
+        if x < self.left || x > self.right || y < self.top || y > self.bottom {
+            None
+        } else if y == self.top && x < self.right {
+            Some(x - self.left)
+        } else if x == self.right && y < self.bottom {
+            Some(width + y - self.top)
+        } else if y == self.bottom && x > self.left {
+            Some(width + height + self.right - x)
+        } else if x == self.left && y > self.top {
+            Some(width * 2 + height + self.bottom - y)
+        } else if (x, y) == (self.left, self.top) {
+            // Perimeter is a single cell
+            Some(0)
+        } else {
+            None
         }
+    }
+
+    /// Returns true if `position` is on the left or right side of the perimeter.
+    fn is_side(&self, (x, y): (usize, usize)) -> bool {
+        (x == self.left || x == self.right) && (self.top..=self.bottom).contains(&y)
+    }
 
-        disable_raw_mode().unwrap();
-        queue!(
-            self.stdout,
-            SetColors(Colors::new(Color::Reset, Color::Reset)),
-            cursor::Show,
-        )?;
-        self.stdout.flush()
+    fn is_marquee_star(&self, position: (usize, usize), frame: usize) -> bool {
+        let len = self.len();
+        match self.index(position) {
+            Some(index) => (index + len - frame % len) % MARQUEE_SPACING == 0,
+            None => false,
+        }
     }
 }
 
-/// Turns the debug string into a grid of chars.  
+/// Enables raw mode and clears the screen. Must be paired with [`leave_screen()`].
+pub(crate) fn enter_screen<W: Write>(stdout: &mut W) -> std::io::Result<()> {
+    enable_raw_mode().unwrap();
+    queue!(
+        stdout,
+        Clear(ClearType::All),
+        MoveTo(0, 0),
+        SetColors(Colors::new(Color::Reset, Color::Reset)),
+        cursor::Hide,
+    )
+}
+
+/// Restores the terminal to the state before [`enter_screen()`] was called.
+pub(crate) fn leave_screen<W: Write>(stdout: &mut W) -> std::io::Result<()> {
+    disable_raw_mode().unwrap();
+    queue!(
+        stdout,
+        SetColors(Colors::new(Color::Reset, Color::Reset)),
+        cursor::Show,
+    )?;
+    stdout.flush()
+}
+
+/// Turns the debug string into a grid of chars. The grid has [`CenteredDebugString::len()`]
+/// cells, row by row from the top left. As an iterator, it never ends: after the last cell, it
+/// keeps returning spaces. Use [`CenteredDebugString::try_next()`] to get an error instead.
 pub struct CenteredDebugString<'chars> {
     char_iter: Chars<'chars>,
     top_margin_length: usize,
@@ -115,7 +482,23 @@ pub struct CenteredDebugString<'chars> {
 }
 
 impl<'chars> CenteredDebugString<'chars> {
+    /// Centers `s` in a grid of `terminal_size` cells.
     pub fn new(s: &'chars str, terminal_size: (usize, usize)) -> Self {
+        Self::new_with_width_fn(s, terminal_size, |s| display_width(s, 2))
+    }
+
+    /// Like [`CenteredDebugString::new()`], but measures the lines of `s` with `display_width`,
+    /// such as [`PrettierConfig::display_width()`].
+    ///
+    /// [`PrettierConfig::display_width()`]: crate::prettier_printer::PrettierConfig::display_width
+    pub fn new_with_width_fn<F>(
+        s: &'chars str,
+        terminal_size: (usize, usize),
+        display_width: F,
+    ) -> Self
+    where
+        F: Fn(&str) -> usize,
+    {
         Self {
             char_iter: s.chars(),
             top_margin_length: CenteredDebugString::margin_length(
@@ -124,7 +507,7 @@ impl<'chars> CenteredDebugString<'chars> {
             ),
             left_margin_length: CenteredDebugString::margin_length(
                 terminal_size.0,
-                CenteredDebugString::longest_line(s),
+                CenteredDebugString::longest_line(s, display_width),
             ),
             curr_index: 0,
             terminal_size,
@@ -132,39 +515,63 @@ impl<'chars> CenteredDebugString<'chars> {
         }
     }
 
-    fn longest_line(s: &str) -> usize {
-        let mut max = 0_usize;
-        let mut curr_line_length = 0_usize;
-        for c in s.chars().chain(once('\n')) {
-            if c == '\n' {
-                if curr_line_length > max {
-                    max = curr_line_length;
-                }
-                curr_line_length = 0;
-            } else {
-                curr_line_length += 1;
-            }
-        }
-        max
+    fn longest_line<F>(s: &str, display_width: F) -> usize
+    where
+        F: Fn(&str) -> usize,
+    {
+        s.split('\n').map(display_width).max().unwrap_or(0)
     }
 
     fn margin_length(max_length: usize, content_length: usize) -> usize {
         (max_length.saturating_sub(content_length)) / 2
     }
 
+    /// Number of cells in the grid. Zero if either dimension of the terminal is zero.
     #[allow(clippy::len_without_is_empty)]
     pub fn len(&self) -> usize {
         self.terminal_size.0 * self.terminal_size.1
     }
+
+    /// Returns the next cell, or an error if all [`CenteredDebugString::len()`] cells have been
+    /// returned.
+    pub fn try_next(&mut self) -> Result<char, CenteredDebugStringError> {
+        if self.curr_index < self.len() {
+            Ok(self.next().unwrap()) // next() always returns Some
+        } else {
+            Err(CenteredDebugStringError::Exhausted { len: self.len() })
+        }
+    }
+}
+
+/// Error returned by [`CenteredDebugString::try_next()`].
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum CenteredDebugStringError {
+    /// Every cell of the grid has been returned.
+    Exhausted { len: usize },
+}
+
+impl std::fmt::Display for CenteredDebugStringError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CenteredDebugStringError::Exhausted { len } => {
+                write!(f, "all {} cells have been returned", len)
+            }
+        }
+    }
 }
 
+impl std::error::Error for CenteredDebugStringError {}
+
 impl Iterator for CenteredDebugString<'_> {
     type Item = char;
 
     fn next(&mut self) -> Option<Self::Item> {
         const SPACE: char = ' ';
 
-        let result = if self.curr_index / self.terminal_size.0 < self.top_margin_length {
+        let result = if self.curr_index >= self.len() {
+            // Past the last cell, including every cell of a grid with a zero dimension
+            SPACE
+        } else if self.curr_index / self.terminal_size.0 < self.top_margin_length {
             // Top margin
             SPACE
         } else if self.curr_index % self.terminal_size.0 < self.left_margin_length {
@@ -194,8 +601,13 @@ impl Iterator for CenteredDebugString<'_> {
 mod tests {
     use super::*;
     use rstest::rstest;
+
+    fn unicode_width(s: &str) -> usize {
+        display_width(s, 2)
+    }
     use std::collections::HashMap;
     use std::io::stdout;
+    use std::rc::Rc;
 
     // #[test]
     #[allow(dead_code)]
@@ -241,11 +653,217 @@ mod tests {
         assert_eq!(result, expected);
     }
 
+    #[rstest]
+    #[case("a", (3, 3))]
+    #[case("abc\ndef", (2, 1))]
+    #[case("a", (0, 0))]
+    #[case("a", (0, 5))]
+    #[case("a", (5, 0))]
+    fn debug_string_grid_past_end(#[case] s: &str, #[case] terminal_size: (usize, usize)) {
+        let mut grid = CenteredDebugString::new(s, terminal_size);
+        let len = grid.len();
+        for _ in 0..len {
+            assert!(grid.try_next().is_ok());
+        }
+        assert_eq!(
+            grid.try_next(),
+            Err(CenteredDebugStringError::Exhausted { len })
+        );
+        assert!((0..100).all(|_| grid.next() == Some(' ')));
+        assert!(grid.try_next().is_err());
+    }
+
+    #[rstest]
+    #[case(true, 0, true)]
+    #[case(true, 1, false)]
+    #[case(false, 0, false)]
+    #[case(false, 1, false)]
+    fn should_revive(
+        #[case] revive_on_extinction: bool,
+        #[case] live_count: usize,
+        #[case] expected: bool,
+    ) {
+        assert_eq!(
+            super::should_revive(revive_on_extinction, live_count),
+            expected
+        );
+    }
+
+    #[test]
+    fn revive_on_extinction_is_opt_in() {
+        assert!(!Sparkles::new(stdout().lock()).revive_on_extinction);
+        assert!(
+            Sparkles::new_with_seed(Seed::default(), stdout().lock())
+                .with_revive_on_extinction(true)
+                .revive_on_extinction
+        );
+    }
+
+    #[rstest]
+    #[case(Duration::from_millis(0), 0)]
+    #[case(Duration::from_millis(49), 1)]
+    #[case(Duration::from_millis(50), 1)]
+    #[case(Duration::from_millis(200), 4)]
+    #[case(Duration::from_millis(201), 5)]
+    fn run_for(#[case] duration: Duration, #[case] expected_generations: usize) {
+        let now = Rc::new(std::cell::Cell::new(Instant::now()));
+        let clock = {
+            let now = now.clone();
+            move || now.get()
+        };
+        // Each frame takes FRAME_DURATION, like the sleep in run_while()
+        let generations = frame_loop(
+            until_expired(duration, clock),
+            || Ok(false),
+            |_| {
+                now.set(now.get() + FRAME_DURATION);
+                Ok(())
+            },
+        )
+        .unwrap();
+        assert_eq!(generations, expected_generations);
+    }
+
+    #[test]
+    fn frame_loop_stop_requested() {
+        let mut frames = Vec::new();
+        let mut polls = 0;
+        let stop_requested = || {
+            polls += 1;
+            Ok(polls > 3)
+        };
+        let count = frame_loop(
+            || Ok(true),
+            stop_requested,
+            |frame| {
+                frames.push(frame);
+                Ok(())
+            },
+        )
+        .unwrap();
+        assert_eq!(count, 3);
+        assert_eq!(frames, [0, 1, 2]);
+    }
+
+    #[rstest]
+    #[case("a", (5, 3))]
+    #[case("ab\ncd", (8, 6))]
+    #[case("abc", (3, 1))]
+    #[case("a", (1, 1))]
+    fn perimeter_index(#[case] s: &str, #[case] terminal_size: (usize, usize)) {
+        let perimeter = Perimeter::new(s, terminal_size, unicode_width);
+        for i in 0..perimeter.len() {
+            assert_eq!(perimeter.index(perimeter.position(i)), Some(i));
+        }
+    }
+
+    #[test]
+    fn marquee_advances() {
+        let perimeter = Perimeter::new("a", (5, 3), unicode_width);
+        assert_eq!(perimeter.len(), 8);
+        for frame in 0..perimeter.len() * 2 {
+            let stars: Vec<usize> = (0..perimeter.len())
+                .filter(|&i| perimeter.is_marquee_star(perimeter.position(i), frame))
+                .collect();
+            let expected: Vec<usize> = (0..perimeter.len())
+                .filter(|i| i % MARQUEE_SPACING == frame % MARQUEE_SPACING)
+                .collect();
+            assert_eq!(stars, expected);
+        }
+        assert!(!perimeter.is_marquee_star((2, 1), 0));
+    }
+
+    #[test]
+    fn frame_shimmer() {
+        assert_ne!(shimmer_color(0, 3), shimmer_color(1, 3));
+        assert_ne!(shimmer_color(0, 3), shimmer_color(0, 4));
+
+        let perimeter = Perimeter::new("a", (5, 3), unicode_width);
+        let sides: Vec<(usize, usize)> = (0..5)
+            .flat_map(|x| (0..3).map(move |y| (x, y)))
+            .filter(|&position| perimeter.is_side(position))
+            .collect();
+        assert_eq!(sides, [(1, 0), (1, 1), (1, 2), (3, 0), (3, 1), (3, 2)]);
+    }
+
+    #[test]
+    fn background_texture() {
+        let texture = super::background_texture([1; 32], 10_000, 0.1);
+        assert_eq!(texture, super::background_texture([1; 32], 10_000, 0.1));
+        assert_ne!(texture, super::background_texture([2; 32], 10_000, 0.1));
+        let count = texture.iter().filter(|&&star| star).count();
+        assert!((900..1100).contains(&count), "{}", count);
+
+        assert!(!super::background_texture([1; 32], 100, 0.0).contains(&true));
+        assert!(!super::background_texture([1; 32], 100, 1.0).contains(&false));
+    }
+
+    #[test]
+    fn text_frames() {
+        let seed = [4; 32];
+        let terminal_size = (20, 8);
+        let frames =
+            Sparkles::new_with_seed(seed, stdout().lock()).text_frames(&"hello", 2, terminal_size);
+        assert_eq!(frames.len(), 2);
+        for frame in &frames {
+            assert_eq!(frame.lines().count(), 8);
+            assert!(frame.lines().all(|line| line.chars().count() == 20));
+            assert!(frame.contains("\"hello\""));
+            assert_eq!(frame, &color::strip_ansi(frame));
+        }
+
+        let board = Board::new(
+            PrettierPrinter::gen_seed(&mut SmallRng::from_seed(seed)),
+            terminal_size,
+        );
+        let next = board.evolve();
+        let text: Vec<char> = CenteredDebugString::new("\"hello\"", (20, 8))
+            .take(20 * 8)
+            .collect();
+        let ticked: Vec<usize> = (0..board.cell_array().len())
+            .filter(|&i| board.cell_array()[i] != next.cell_array()[i] && text[i] == ' ')
+            .collect();
+        let changed: Vec<usize> = frames[0]
+            .lines()
+            .flat_map(|line| line.chars())
+            .zip(frames[1].lines().flat_map(|line| line.chars()))
+            .enumerate()
+            .filter(|(_, (a, b))| a != b)
+            .map(|(i, _)| i)
+            .collect();
+        assert!(!changed.is_empty());
+        assert_eq!(changed, ticked);
+    }
+
     #[test]
     fn longest_line() {
-        assert_eq!(CenteredDebugString::longest_line(""), 0);
-        assert_eq!(CenteredDebugString::longest_line("\n"), 0);
-        assert_eq!(CenteredDebugString::longest_line("1\n"), 1);
-        assert_eq!(CenteredDebugString::longest_line("\n1"), 1);
+        assert_eq!(CenteredDebugString::longest_line("", unicode_width), 0);
+        assert_eq!(CenteredDebugString::longest_line("\n", unicode_width), 0);
+        assert_eq!(CenteredDebugString::longest_line("1\n", unicode_width), 1);
+        assert_eq!(CenteredDebugString::longest_line("\n1", unicode_width), 1);
+        assert_eq!(
+            CenteredDebugString::longest_line("日本\na", unicode_width),
+            4
+        );
+        assert_eq!(
+            CenteredDebugString::longest_line("日本\na", |s| s.chars().count()),
+            2
+        );
+    }
+
+    #[test]
+    fn with_width_fn() {
+        let terminal_size = (10, 3);
+        let centered = |width_fn: Option<fn(char) -> usize>| -> String {
+            let mut sparkles = Sparkles::new_with_seed([0; 32], stdout().lock());
+            if let Some(width_fn) = width_fn {
+                sparkles = sparkles.with_width_fn(width_fn);
+            }
+            let frame = sparkles.text_frames(&"日本", 1, terminal_size).remove(0);
+            frame.lines().nth(1).unwrap().replace(TEXT_LIVE_CELL, " ")
+        };
+        // "\"日本\"" is 6 columns wide, or 4 if each character is one column
+        assert!(centered(None).starts_with("  \"日本\""));
+        assert!(centered(Some(|_| 1)).starts_with("   \"日本\""));
     }
 }
@@ -0,0 +1,231 @@
+//! Prints `tracing` events in boxes. Requires the `tracing-subscriber` feature.
+//!
+//! ```
+//! use prettier_print::prettier_printer::PrettierPrinter;
+//! use prettier_print::tracing_format::PrettierFormat;
+//!
+//! tracing_subscriber::fmt()
+//!     .event_format(PrettierFormat::new(PrettierPrinter::default()))
+//!     .init();
+//! ```
+
+use crate::color;
+use crate::prettier_printer::{ColorChoice, PrettierPrinter};
+use crossterm::style::Color;
+use std::fmt::{Debug, Write};
+use std::sync::Mutex;
+use tracing_core::field::{Field, Visit};
+use tracing_core::{Event, Level, Subscriber};
+use tracing_subscriber::fmt::format::Writer;
+use tracing_subscriber::fmt::{FmtContext, FormatEvent, FormatFields, FormattedFields};
+use tracing_subscriber::registry::LookupSpan;
+
+/// Formats each event's level, spans, message, and fields in a box, like
+/// `INFO outer{id=1}:inner: message`. The corners of the box are colored squares that indicate the
+/// level, and the first line is written in the color of the level. With [`ColorChoice::Auto`],
+/// the line is colored if the subscriber writes ANSI escape codes.
+#[derive(Debug)]
+pub struct PrettierFormat {
+    printer: Mutex<PrettierPrinter>,
+}
+
+impl PrettierFormat {
+    pub fn new(printer: PrettierPrinter) -> Self {
+        Self {
+            printer: Mutex::new(printer),
+        }
+    }
+}
+
+impl Default for PrettierFormat {
+    fn default() -> Self {
+        Self::new(PrettierPrinter::default())
+    }
+}
+
+impl<S, N> FormatEvent<S, N> for PrettierFormat
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    N: for<'a> FormatFields<'a> + 'static,
+{
+    fn format_event(
+        &self,
+        ctx: &FmtContext<'_, S, N>,
+        mut writer: Writer<'_>,
+        event: &Event<'_>,
+    ) -> std::fmt::Result {
+        let mut visitor = FieldVisitor::default();
+        event.record(&mut visitor);
+
+        let level = event.metadata().level();
+        let mut first_line = format!("{} ", level);
+        // Spans from the outermost one, such as "outer{id=1}:inner: "
+        if let Some(scope) = ctx.event_scope() {
+            for span in scope.from_root() {
+                first_line.push_str(span.name());
+                let extensions = span.extensions();
+                if let Some(fields) = extensions.get::<FormattedFields<N>>() {
+                    if !fields.is_empty() {
+                        write!(first_line, "{{{}}}", fields)?;
+                    }
+                }
+                first_line.push(':');
+            }
+            if first_line.ends_with(':') {
+                first_line.push(' ');
+            }
+        }
+        first_line.push_str(&visitor.message);
+        let mut text = first_line.clone();
+        for (name, value) in visitor.fields {
+            write!(text, "\n    {} = {}", name, value)?;
+        }
+
+        let mut printer = self.printer.lock().unwrap_or_else(|e| e.into_inner());
+        let color = match printer.config().color {
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+            ColorChoice::Auto => writer.has_ansi_escapes(),
+        };
+        let mut displayer = printer.print_display(&text);
+        displayer.set_border(level_border(level));
+        let mut output = displayer.to_string();
+        if color {
+            let mut colored = String::new();
+            color::push_colored_str(&mut colored, &first_line, level_color(level));
+            output = output.replacen(&first_line, &colored, 1);
+        }
+        writer.write_str(&output)
+    }
+}
+
+fn level_border(level: &Level) -> char {
+    match *level {
+        Level::ERROR => '🟥',
+        Level::WARN => '🟨',
+        Level::INFO => '🟩',
+        Level::DEBUG => '🟦',
+        Level::TRACE => '🟪',
+    }
+}
+
+fn level_color(level: &Level) -> Color {
+    match *level {
+        Level::ERROR => Color::Red,
+        Level::WARN => Color::Yellow,
+        Level::INFO => Color::Green,
+        Level::DEBUG => Color::Blue,
+        Level::TRACE => Color::Magenta,
+    }
+}
+
+#[derive(Debug, Default)]
+struct FieldVisitor {
+    message: String,
+    fields: Vec<(&'static str, String)>,
+}
+
+impl Visit for FieldVisitor {
+    fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+        if field.name() == "message" {
+            self.message = format!("{:?}", value);
+        } else {
+            self.fields.push((field.name(), format!("{:?}", value)));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prettier_printer::Seed;
+    use rstest::rstest;
+    use std::io;
+    use std::sync::Arc;
+
+    #[derive(Clone, Default)]
+    struct Buffer(Arc<Mutex<Vec<u8>>>);
+
+    impl io::Write for Buffer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    /// Output of the events logged by `log`.
+    fn format<F>(printer: PrettierPrinter, log: F) -> String
+    where
+        F: FnOnce(),
+    {
+        let buffer = Buffer::default();
+        let subscriber = {
+            let buffer = buffer.clone();
+            tracing_subscriber::fmt()
+                .event_format(PrettierFormat::new(printer))
+                .with_writer(move || buffer.clone())
+                .finish()
+        };
+        tracing::subscriber::with_default(subscriber, log);
+        let result = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+        result
+    }
+
+    #[test]
+    fn format_event() {
+        let printer =
+            PrettierPrinter::new_with_seed(Seed::default()).with_color(ColorChoice::Never);
+        let result = format(printer, || {
+            tracing::info!(answer = 42, "hello");
+        });
+        let lines: Vec<&str> = result.lines().collect();
+        assert_eq!(lines.len(), 4);
+        assert!(lines[0].starts_with('🟩') && lines[0].ends_with('🟩'));
+        assert!(lines[1].contains("INFO hello"));
+        assert!(lines[2].contains("answer = 42"));
+        assert!(lines[3].starts_with('🟩') && lines[3].ends_with('🟩'));
+        assert!(!result.contains('\x1b'));
+    }
+
+    #[test]
+    fn spans() {
+        let printer =
+            PrettierPrinter::new_with_seed(Seed::default()).with_color(ColorChoice::Never);
+        let result = format(printer, || {
+            let _outer = tracing::info_span!("outer", id = 1).entered();
+            let _inner = tracing::info_span!("inner").entered();
+            tracing::warn!("hello");
+        });
+        let lines: Vec<&str> = result.lines().collect();
+        assert!(
+            lines[1].contains("WARN outer{id=1}:inner: hello"),
+            "{}",
+            result
+        );
+    }
+
+    #[rstest]
+    #[case(Level::ERROR)]
+    #[case(Level::INFO)]
+    #[case(Level::WARN)]
+    fn level_color(#[case] level: Level) {
+        let printer =
+            PrettierPrinter::new_with_seed(Seed::default()).with_color(ColorChoice::Always);
+        let result = format(printer, || match level {
+            Level::ERROR => tracing::error!("hello"),
+            Level::INFO => tracing::info!("hello"),
+            _ => tracing::warn!("hello"),
+        });
+        let mut expected = String::new();
+        color::push_colored_str(
+            &mut expected,
+            &format!("{} hello", level),
+            super::level_color(&level),
+        );
+        let lines: Vec<&str> = result.lines().collect();
+        assert!(lines[1].contains(&expected), "{:?}", result);
+    }
+}
@@ -0,0 +1,143 @@
+use crate::prettier_printer::{
+    seed_from_bytes, DecorationPlan, PrettierConfig, PrettierPrinter, Seed,
+};
+use crate::sparkles::{enter_screen, leave_screen};
+use crossterm::cursor::{MoveTo, MoveToNextLine};
+use crossterm::event::poll;
+use crossterm::queue;
+use crossterm::style::Print;
+use crossterm::terminal::{Clear, ClearType};
+use rand::rngs::SmallRng;
+use rand::SeedableRng;
+use std::fmt::Debug;
+use std::io::{StdoutLock, Write};
+use std::thread::sleep;
+use std::time::Duration;
+
+/// Prints the decorated debug string and makes the stars twinkle: each frame, the stars appear,
+/// disappear, and change glyph while the content stays in place.
+pub struct Twinkle<'stream> {
+    rng: SmallRng,
+    stdout: StdoutLock<'stream>,
+    config: PrettierConfig,
+}
+
+impl<'stream> Twinkle<'stream> {
+    /// Initializes with random seed.
+    pub fn new(stdout: StdoutLock<'stream>) -> Self {
+        Self {
+            rng: SmallRng::from_entropy(),
+            stdout,
+            config: PrettierConfig::default(),
+        }
+    }
+
+    pub fn new_with_seed(seed: Seed, stdout: StdoutLock<'stream>) -> Self {
+        Self {
+            rng: SmallRng::from_seed(seed),
+            stdout,
+            config: PrettierConfig::default(),
+        }
+    }
+
+    /// Sets how each frame is decorated. The stars are planned for each frame, so settings that
+    /// decide where random stars go, such as the star location and density, are not used.
+    ///
+    /// # Panics
+    ///
+    /// Panics if [`PrettierPrinter::try_with_config()`] would return an error.
+    pub fn with_config(mut self, config: PrettierConfig) -> Self {
+        if let Err(e) = config.validate() {
+            panic!("{}", e);
+        }
+        self.config = config;
+        self
+    }
+
+    /// Shows `frames` frames. Press any key to stop early.
+    pub fn run<T>(&mut self, what: &T, frames: usize) -> std::io::Result<()>
+    where
+        T: Debug,
+    {
+        enter_screen(&mut self.stdout)?;
+
+        let debug_str = format!("{:#?}", what);
+        let content_width = debug_str
+            .lines()
+            .map(|line| self.config.display_width(line))
+            .max()
+            .unwrap_or(0);
+        let height = debug_str.lines().count();
+        let seed = PrettierPrinter::gen_seed(&mut self.rng);
+
+        for frame in 0..frames {
+            if poll(Duration::from_secs(0))? {
+                break;
+            }
+            let plan = frame_plan(&self.config, seed, frame, content_width, height);
+            queue!(self.stdout, MoveTo(0, 0))?;
+            for line in self.config.apply_plan(&plan, &debug_str).lines() {
+                queue!(
+                    self.stdout,
+                    Print(line),
+                    Clear(ClearType::UntilNewLine),
+                    MoveToNextLine(1),
+                )?;
+            }
+            self.stdout.flush()?;
+
+            sleep(FRAME_DURATION);
+        }
+
+        leave_screen(&mut self.stdout)
+    }
+}
+
+const FRAME_DURATION: Duration = Duration::from_millis(200);
+
+/// Stars of `frame`. Each frame is planned with its own seed derived from `seed`, so frames differ
+/// from each other but the same frame is always the same.
+fn frame_plan(
+    config: &PrettierConfig,
+    seed: Seed,
+    frame: usize,
+    content_width: usize,
+    height: usize,
+) -> DecorationPlan {
+    let frame_seed = seed_from_bytes(&(frame as u64).to_le_bytes());
+    config.plan(
+        PrettierPrinter::mix_seeds(seed, frame_seed),
+        content_width,
+        height,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frame_plan() {
+        let config = PrettierConfig::default();
+        let debug_str = format!("{:#?}", vec![10, 20, 30, 40, 50]);
+        let content_width = 7;
+        let height = debug_str.lines().count();
+        let plans: Vec<DecorationPlan> = (0..5)
+            .map(|frame| super::frame_plan(&config, [1; 32], frame, content_width, height))
+            .collect();
+        assert_eq!(
+            plans[0],
+            super::frame_plan(&config, [1; 32], 0, content_width, height)
+        );
+        assert!(plans.windows(2).all(|pair| pair[0] != pair[1]));
+
+        for plan in &plans {
+            let output = config.apply_plan(plan, &debug_str);
+            let lines: Vec<&str> = output.lines().collect();
+            assert_eq!(lines.len(), height + 2);
+            for (line, content) in lines[1..lines.len() - 1].iter().zip(debug_str.lines()) {
+                assert!(line.starts_with(&format!(" {}", content)));
+            }
+        }
+    }
+}
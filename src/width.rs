@@ -0,0 +1,79 @@
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthChar;
+
+/// Number of columns `s` takes up in a terminal. Emojis are counted as `emoji_width` columns.
+pub(crate) fn display_width(s: &str, emoji_width: usize) -> usize {
+    s.chars().map(|c| char_width(c, emoji_width)).sum()
+}
+
+/// Returns true if `s` only contains printable ASCII characters and line breaks, which are each
+/// one column wide.
+pub(crate) fn is_printable_ascii(s: &str) -> bool {
+    s.bytes().all(|b| b == b'\n' || (b' '..=b'~').contains(&b))
+}
+
+/// Number of columns `c` takes up in a terminal. Emojis are counted as `emoji_width` columns.
+pub(crate) fn char_width(c: char, emoji_width: usize) -> usize {
+    match c.width().unwrap_or(0) {
+        2 if is_emoji(c) => emoji_width,
+        n => n,
+    }
+}
+
+/// Longest prefix of `s` that is at most `max_columns` wide, measuring each grapheme cluster with
+/// `width`. Never splits a grapheme cluster, so combining marks stay with the character they
+/// modify.
+pub(crate) fn safe_truncate<F>(s: &str, max_columns: usize, width: F) -> &str
+where
+    F: Fn(&str) -> usize,
+{
+    let mut total = 0;
+    for (i, grapheme) in s.grapheme_indices(true) {
+        total += width(grapheme);
+        if total > max_columns {
+            return &s[..i];
+        }
+    }
+    s
+}
+
+fn is_emoji(c: char) -> bool {
+    // Symbols and dingbats, arrows and shapes such as ⭐, and emojis
+    matches!(c as u32, 0x2600..=0x27BF | 0x2B00..=0x2BFF | 0x1F000..=0x1FAFF)
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("", 2, 0)]
+    #[case("abc", 2, 3)]
+    #[case("🌈a🌈", 2, 5)]
+    #[case("🌈a🌈", 1, 3)]
+    #[case("⭐", 1, 1)]
+    #[case("日本", 1, 4)]
+    #[case("\u{301}", 2, 0)]
+    #[case("-170141183460469231731687303715884105728", 2, 40)]
+    #[case("a\tb", 2, 2)]
+    fn display_width(#[case] s: &str, #[case] emoji_width: usize, #[case] expected: usize) {
+        assert_eq!(super::display_width(s, emoji_width), expected);
+    }
+
+    #[rstest]
+    #[case("abc", 5, "abc")]
+    #[case("abc", 2, "ab")]
+    #[case("a🌈b", 2, "a")]
+    #[case("a🌈b", 3, "a🌈")]
+    #[case("日本語", 3, "日")]
+    #[case("日本語", 0, "")]
+    #[case("ae\u{301}", 1, "a")]
+    #[case("ae\u{301}b", 2, "ae\u{301}")]
+    #[case("", 0, "")]
+    fn safe_truncate(#[case] s: &str, #[case] max_columns: usize, #[case] expected: &str) {
+        let result =
+            super::safe_truncate(s, max_columns, |grapheme| super::display_width(grapheme, 2));
+        assert_eq!(result, expected);
+        assert!(super::display_width(result, 2) <= max_columns);
+    }
+}